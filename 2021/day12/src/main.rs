@@ -1,5 +1,6 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
+mod paths;
+
+use paths::{Constraints, Digraph};
 use std::fs::File;
 use std::io::prelude::*;
 use std::result::Result;
@@ -57,109 +58,35 @@ fn main() -> Result<(), AppErr> {
     Ok(())
 }
 
-type Graph = HashMap<String, HashSet<String>>;
-
-fn part1( input: &Vec<Link>) -> u32 {
-    
-    fn add_link(graph: &mut Graph, from: &str, to: &str) {
-
-        if to == "start" || from == "end" {
-            return
-        }
-
-        graph.entry(from.to_string()).or_default().insert(to.to_string());
-
-    }
-
-    let mut graph = Graph::new();
-    for link in input {
-        add_link( &mut graph, &link.0, &link.1);
-        add_link( &mut graph, &link.1, &link.0);
-    }
-
-    fn explore(graph: &Graph, visited: &mut HashSet<String>, cave: &str) -> u32 {
-
-        if cave == "end" {
-            return 1;
-        }
-        
-        visited.insert(cave.to_string());
-
-        let mut paths = 0;
-
-
-        let d = HashSet::new();
-
-        for connected in graph.get(cave).unwrap_or(&d) {
-            
-            if connected.chars().all(char::is_lowercase) && visited.get(connected).is_some() {
-                continue
-            }
-
-            paths += explore(graph, visited, connected);
-        }
-
-        visited.remove(cave);
-
-        paths
-    }
-
-    let mut visited = HashSet::new();
-    explore(&graph, &mut visited, "start")
+// Passages lead both ways, except back into the start or out of the end
+fn graph(input: &[Link]) -> Digraph<&str> {
+    input
+        .iter()
+        .flat_map(|(a, b)| vec![(a.as_str(), b.as_str()), (b.as_str(), a.as_str())])
+        .filter(|&(from, to)| to != "start" && from != "end")
+        .collect()
 }
 
+fn small(cave: &str) -> bool {
+    cave.chars().all(char::is_lowercase)
+}
 
-fn part2( input: &Vec<Link>) -> u32 {
-    
-    fn add_link(graph: &mut Graph, from: &str, to: &str) {
-
-        if to == "start" || from == "end" {
-            return
-        }
-
-        graph.entry(from.to_string()).or_default().insert(to.to_string());
-
-    }
-
-    let mut graph = Graph::new();
-    for link in input {
-        add_link( &mut graph, &link.0, &link.1);
-        add_link( &mut graph, &link.1, &link.0);
-    }
-
-    fn explore(graph: &Graph, visited: &mut HashMap<String, u8>, once: bool, cave: &str) -> u32 {
-
-        if cave == "end" {
-            return 1;
-        }
-        
-        *visited.entry(cave.to_string()).or_insert(0) += 1;
-
-        let mut paths = 0;
-
-
-        let d = HashSet::new();
-
-        for connected in graph.get(cave).unwrap_or(&d) {
-            
-            let mut joker = once;
-
-            if *visited.get(connected).unwrap_or(&0) != 0 && connected.chars().all(char::is_lowercase) {
-                if once {
-                    continue
-                } else {
-                    joker = true;
-                }
-            }
-
-            paths += explore(graph, visited, joker,connected);
-        }
-
-        *(visited.get_mut(cave).unwrap()) -= 1;
-
-        paths
-    }
+fn part1(input: &[Link]) -> u32 {
+    let paths = graph(input).count_walks("start", "end", &Constraints::default(), |path, cave| {
+        !small(cave) || !path.contains(&cave)
+    });
+    paths as u32
+}
 
-    let mut visited = HashMap::new();
-    explore(&graph, &mut visited, false,"start")
-}
\ No newline at end of file
+fn part2(input: &[Link]) -> u32 {
+    // A single small cave may be visited twice
+    let twice = |path: &[&str]| {
+        path.iter()
+            .enumerate()
+            .any(|(i, cave)| small(cave) && path[..i].contains(cave))
+    };
+    let paths = graph(input).count_walks("start", "end", &Constraints::default(), |path, cave| {
+        !small(cave) || !path.contains(&cave) || !twice(path)
+    });
+    paths as u32
+}
@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+/// Directed graph stored as successor lists.
+#[derive(Debug, Clone)]
+pub struct Digraph<N> {
+    edges: HashMap<N, Vec<N>>,
+}
+
+/// Restrictions on the paths counted by `Digraph::count_walks`.
+#[derive(Debug, Clone)]
+pub struct Constraints<N> {
+    /// Nodes every path has to pass through, in any order.
+    pub must: Vec<N>,
+    /// Nodes no path may enter.
+    pub avoid: HashSet<N>,
+}
+
+impl<N> Default for Constraints<N> {
+    fn default() -> Self {
+        Constraints {
+            must: Vec::new(),
+            avoid: HashSet::new(),
+        }
+    }
+}
+
+impl<N: Copy + Hash + Eq> Digraph<N> {
+    pub fn new() -> Self {
+        Digraph {
+            edges: HashMap::new(),
+        }
+    }
+
+    pub fn add_edge(&mut self, a: N, b: N) {
+        self.edges.entry(a).or_default().push(b);
+    }
+
+    pub fn successors(&self, n: N) -> &[N] {
+        self.edges.get(&n).map_or(&[], Vec::as_slice)
+    }
+
+    /// Count paths by depth first enumeration. A step to `n` is only taken if `allow` accepts
+    /// it given the path so far, which must be enough to keep paths on cyclic graphs finite.
+    pub fn count_walks(
+        &self,
+        from: N,
+        to: N,
+        constraints: &Constraints<N>,
+        mut allow: impl FnMut(&[N], N) -> bool,
+    ) -> i64 {
+        fn walk<N: Copy + Hash + Eq>(
+            graph: &Digraph<N>,
+            path: &mut Vec<N>,
+            to: N,
+            constraints: &Constraints<N>,
+            allow: &mut impl FnMut(&[N], N) -> bool,
+        ) -> i64 {
+            let n = *path.last().unwrap();
+            if n == to {
+                return constraints.must.iter().all(|m| path.contains(m)) as i64;
+            }
+
+            let mut count = 0;
+            for &s in graph.successors(n) {
+                if constraints.avoid.contains(&s) || !allow(path, s) {
+                    continue;
+                }
+                path.push(s);
+                count += walk(graph, path, to, constraints, allow);
+                path.pop();
+            }
+            count
+        }
+
+        if constraints.avoid.contains(&from) {
+            return 0;
+        }
+        walk(self, &mut vec![from], to, constraints, &mut allow)
+    }
+}
+
+impl<N: Copy + Hash + Eq> FromIterator<(N, N)> for Digraph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(iter: I) -> Self {
+        let mut graph = Digraph::new();
+        for (a, b) in iter {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+}
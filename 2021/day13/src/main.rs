@@ -4,7 +4,9 @@ use std::io::prelude::*;
 use std::num::ParseIntError;
 use std::result::Result;
 use std::fmt::{Debug};
-use std::cmp::{min,max};
+
+mod ocr;
+use ocr::ocr;
 
 #[derive(Debug)]
 enum AppErr {
@@ -85,7 +87,11 @@ fn main() -> Result<(), AppErr> {
     let input = parse(&read_file("input.txt")? )?;
     println!("{}", part1( &input ));
 
-    part2(&input);
+    println!("{:?}", part2( &input ));
+    assert!(part2( &input ) == Some("ZUJUAFHP".to_string()));
+
+    // The example folds into a square, which is no letter
+    assert!(part2( &test1 ).is_none());
 
     Ok(())
 }
@@ -107,31 +113,9 @@ fn part1(input: &Input) -> u32 {
     points.len() as u32
 }
 
-fn part2(input: &Input) {
+fn part2(input: &Input) -> Option<String> {
 
     let points = input.folds.iter().fold( input.points.clone(), |set, f| fold(set, f) );
 
-    let mut x_min = 1000;
-    let mut x_max = -1000;
-    let mut y_min = 1000;
-    let mut y_max = -1000;
-
-    for &(x,y) in &points {
-        x_min = min(x_min, x);
-        x_max = max(x_max, x);
-
-        y_min = min(y_min, y);
-        y_max = max(y_max, y);
-    }
-
-    for y in y_min..=y_max {
-        for x in x_min..=x_max {
-            if points.get(&(x,y)).is_some() {
-                print!("#");
-            } else {
-                print!(" ");
-            }
-        }
-        println!("");
-    }
-}
\ No newline at end of file
+    ocr(points.into_iter().map(|(x, y)| (x as i64, y as i64)))
+}
@@ -1,3 +1,6 @@
+mod packet;
+
+use packet::{DecodeError, EncodeError, Length, Op, Packet};
 use std::fs::File;
 use std::io::prelude::*;
 use std::result::Result;
@@ -6,6 +9,7 @@ use std::fmt::{Debug};
 #[derive(Debug)]
 enum AppErr {
     IoError(std::io::Error),
+    DecodeError(DecodeError),
 }
 
 impl From<std::io::Error> for AppErr {
@@ -14,6 +18,12 @@ impl From<std::io::Error> for AppErr {
     }
 }
 
+impl From<DecodeError> for AppErr {
+    fn from(err: DecodeError) -> Self {
+        Self::DecodeError(err)
+    }
+}
+
 fn read_file(path: &str) -> Result<String, AppErr> {
     let mut file = File::open(path)?;
     let mut contents = String::new();
@@ -21,24 +31,8 @@ fn read_file(path: &str) -> Result<String, AppErr> {
     Ok(contents)
 }
 
-fn parse(input: &str) -> Result<Vec<bool>, AppErr> {
-
-    let mut bits = Vec::<bool>::new();
-    for c in input.chars() {
-
-        if !c.is_ascii_hexdigit() {
-            break;
-        }
-
-        let nibble = c.to_digit(16).unwrap();
-
-        bits.push(nibble & 0b1000 != 0);
-        bits.push(nibble & 0b0100 != 0);
-        bits.push(nibble & 0b0010 != 0);
-        bits.push(nibble & 0b0001 != 0);
-    }
-
-    Ok(bits)
+fn parse(input: &str) -> Result<Packet, AppErr> {
+    Ok(Packet::decode(input)?)
 }
 
 fn main() -> Result<(), AppErr> {
@@ -65,153 +59,34 @@ fn main() -> Result<(), AppErr> {
  
     println!("{}", part2(&input));
 
-    Ok(())
-}
-
-fn take_num(slice: &mut &[bool], len: usize) -> u64 {
-    let mut n = 0;
-    for i in 0..len {
-        n = n << 1;
-        if slice[i] {
-            n |= 1;
-        }
+    // Encoding gives back the transmission, and decoding that the same packets
+    for hex in ["D2FE28", "38006F45291200", "EE00D40C823060", "9C0141080250320F1802104A08"] {
+        assert!(parse(hex)?.encode() == Ok(hex.to_string()));
     }
-    *slice = &slice[len..];
-    n
-}
+    assert!(parse(&input.encode().unwrap())? == input);
 
-fn take_slice<'a>(slice: &mut &'a[bool], len: usize) -> &'a[bool] {
-    let res = &slice[0..len];
-    *slice = &slice[len..];
-    res
-}
+    // Fields too large for the format can't be encoded
+    let one = Packet::Literal { version: 0, value: 1 };
+    assert!(Packet::Literal { version: 8, value: 1 }.encode() == Err(EncodeError::Version(8)));
+    let many = Packet::Operator { version: 0, op: Op::Sum, length: Length::Count, packets: vec![one.clone(); 2048] };
+    assert!(many.encode() == Err(EncodeError::Count(2048)));
+    let long = Packet::Operator { version: 0, op: Op::Sum, length: Length::Bits, packets: vec![one; 3000] };
+    assert!(long.encode() == Err(EncodeError::Length(3000 * 11)));
 
+    assert!(parse("9C0141080250320F1802104A08")?.to_string() == "(= (+ 1 3) (* 2 2))");
+    assert!(format!("{:#}", parse("9C0141080250320F1802104A08")?) == "(=\n  (+ 1 3)\n  (* 2 2))");
 
-fn part1(bits: &[bool]) -> u64 {
-
-    
-    let mut stream = bits;
-
-
-    fn packet(stream: &mut &[bool]) -> u64 {
-        let mut sum = 0;
-
-        let version = take_num(stream, 3);
-        let id_type = take_num(stream, 3);
-
-        sum += version;
-
-        match id_type {
-            4 => {
-                loop {
-                    let group = take_num(stream, 5);
-                    if group & 0b10000 == 0 {
-                        break;
-                    }
-                } 
-            },
-            _ => {
-                let len_type = take_num(stream, 1);         
-                match len_type {
-                    0 => {
-                        let len = take_num(stream, 15);
-                        let mut sub = take_slice(stream, len as usize);
-                        while sub.len() != 0 {
-                            sum += packet(&mut sub);
-                        }
-                    },
-                    1 => {
-                        let len = take_num(stream, 11);
-                        for _ in 0..len {
-                            sum += packet(stream);
-                        }
-                    },
-                    _ => ()
-                }
-            }
-        }
-
-        sum
-    }
+    assert!(matches!(parse("D2FE2"), Err(AppErr::DecodeError(DecodeError::Truncated { at: 20, need: 1 }))));
+    assert!(matches!(parse("D2XE28"), Err(AppErr::DecodeError(DecodeError::Hex('X')))));
+    assert!(matches!(parse("0A000"), Err(AppErr::DecodeError(DecodeError::Operands { at: 0, count: 0 }))));
 
-    packet(&mut stream)
+    Ok(())
 }
 
-fn part2(bits: &[bool]) -> u64 {
-
-    
-    let mut stream = bits;
-
-    fn packets(stream: &mut &[bool]) -> Vec<u64> {
-        let len_type = take_num(stream, 1);   
-        let mut res = Vec::new();
-        match len_type {
-            0 => {
-                let len = take_num(stream, 15);
-                let mut sub = take_slice(stream, len as usize);
-                while sub.len() != 0 {
-                    res.push( packet(&mut sub) );
-                }
-            },
-            1 => {
-                let len = take_num(stream, 11);
-                for _ in 0..len {
-                    res.push( packet(stream) );
-                }
-            },
-            _ => panic!()
-        }
-
-        res
-    }
-
-
-    fn packet(stream: &mut &[bool]) -> u64 {
-        let mut n;
-
-        let _version = take_num(stream, 3);
-        let id_type = take_num(stream, 3);
-
-        match id_type {
-            4 => {
-                n = 0;
-                loop {
-                    let group = take_num(stream, 5);
-                    n = (n << 4) | (group & 0xF);
-                    if group & 0b10000 == 0 {
-                        break;
-                    }
-                }
-            },
-            0 => {         
-                n = packets(stream).iter().sum();
-            },
-            1 => {         
-                n = packets(stream).iter().product();
-            },
-            2 => {         
-                n = *packets(stream).iter().min().unwrap();
-            },
-            3 => {         
-                n = *packets(stream).iter().max().unwrap();
-            },
-            5 => {         
-                let p = packets(stream);
-                n = (p[0] > p[1]) as u64; 
-            },
-            6 => {         
-                let p = packets(stream);
-                n = (p[0] < p[1]) as u64; 
-            },
-            7 => {         
-                let p = packets(stream);
-                n = (p[0] == p[1]) as u64; 
-            },
-            _ => panic!()
-        }
-
-        n
-    }
+fn part1(packet: &Packet) -> u64 {
+    packet.version_sum()
+}
 
-    packet(&mut stream)
-}
\ No newline at end of file
+fn part2(packet: &Packet) -> u64 {
+    packet.eval()
+}
@@ -0,0 +1,348 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    Greater,
+    Less,
+    Equal,
+}
+
+impl Op {
+    fn from_type(id: u64) -> Option<Op> {
+        match id {
+            0 => Some(Op::Sum),
+            1 => Some(Op::Product),
+            2 => Some(Op::Minimum),
+            3 => Some(Op::Maximum),
+            5 => Some(Op::Greater),
+            6 => Some(Op::Less),
+            7 => Some(Op::Equal),
+            _ => None,
+        }
+    }
+
+    fn type_id(&self) -> u64 {
+        match self {
+            Op::Sum => 0,
+            Op::Product => 1,
+            Op::Minimum => 2,
+            Op::Maximum => 3,
+            Op::Greater => 5,
+            Op::Less => 6,
+            Op::Equal => 7,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Op::Sum => "+",
+            Op::Product => "*",
+            Op::Minimum => "min",
+            Op::Maximum => "max",
+            Op::Greater => ">",
+            Op::Less => "<",
+            Op::Equal => "=",
+        }
+    }
+}
+
+/// How an operator packet says where its sub-packets end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Length {
+    /// Total number of bits in the sub-packets, type 0.
+    Bits,
+    /// Number of sub-packets, type 1.
+    Count,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Literal {
+        version: u8,
+        value: u64,
+    },
+    Operator {
+        version: u8,
+        op: Op,
+        length: Length,
+        packets: Vec<Packet>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A character that isn't a hex digit.
+    Hex(char),
+    /// The stream ended at bit `at` while `need` more bits were expected.
+    Truncated { at: usize, need: usize },
+    /// An operator at bit `at` with no sub-packets, or a comparison without exactly two.
+    Operands { at: usize, count: usize },
+    /// A literal at bit `at` too large for 64 bits.
+    Overflow { at: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Hex(c) => write!(f, "{:?} is not a hex digit", c),
+            DecodeError::Truncated { at, need } => {
+                write!(f, "stream ends at bit {}, {} more needed", at, need)
+            }
+            DecodeError::Operands { at, count } => {
+                write!(f, "operator at bit {} has {} operands", at, count)
+            }
+            DecodeError::Overflow { at } => write!(f, "literal at bit {} overflows", at),
+        }
+    }
+}
+
+/// Why a packet can't be written in the transmission format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// A version that doesn't fit in 3 bits.
+    Version(u8),
+    /// More sub-packets than the 11 bit count holds.
+    Count(usize),
+    /// Sub-packets taking more bits than the 15 bit length holds.
+    Length(usize),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::Version(v) => write!(f, "version {} doesn't fit in 3 bits", v),
+            EncodeError::Count(n) => write!(f, "{} sub-packets don't fit in an 11 bit count", n),
+            EncodeError::Length(n) => write!(f, "{} bits of sub-packets don't fit in 15 bits", n),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bits: &'a [bool],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<u64, DecodeError> {
+        if self.pos + len > self.bits.len() {
+            return Err(DecodeError::Truncated {
+                at: self.bits.len(),
+                need: self.pos + len - self.bits.len(),
+            });
+        }
+        let n = self.bits[self.pos..self.pos + len]
+            .iter()
+            .fold(0, |n, &bit| n << 1 | bit as u64);
+        self.pos += len;
+        Ok(n)
+    }
+
+    fn packet(&mut self) -> Result<Packet, DecodeError> {
+        let start = self.pos;
+        let version = self.take(3)? as u8;
+        let type_id = self.take(3)?;
+
+        let op = match Op::from_type(type_id) {
+            Some(op) => op,
+            None => {
+                let mut value: u64 = 0;
+                loop {
+                    let group = self.take(5)?;
+                    if value.leading_zeros() < 4 {
+                        return Err(DecodeError::Overflow { at: start });
+                    }
+                    value = value << 4 | (group & 0xF);
+                    if group & 0b10000 == 0 {
+                        return Ok(Packet::Literal { version, value });
+                    }
+                }
+            }
+        };
+
+        let mut packets = Vec::new();
+        let length = if self.take(1)? == 0 {
+            let len = self.take(15)? as usize;
+            let end = self.pos + len;
+            if end > self.bits.len() {
+                return Err(DecodeError::Truncated {
+                    at: self.bits.len(),
+                    need: end - self.bits.len(),
+                });
+            }
+            // Sub-packets can't read past the length given
+            let mut sub = Reader {
+                bits: &self.bits[..end],
+                pos: self.pos,
+            };
+            while sub.pos < end {
+                packets.push(sub.packet()?);
+            }
+            self.pos = end;
+            Length::Bits
+        } else {
+            for _ in 0..self.take(11)? {
+                packets.push(self.packet()?);
+            }
+            Length::Count
+        };
+
+        let comparison = matches!(op, Op::Greater | Op::Less | Op::Equal);
+        if packets.is_empty() || comparison && packets.len() != 2 {
+            return Err(DecodeError::Operands {
+                at: start,
+                count: packets.len(),
+            });
+        }
+        Ok(Packet::Operator {
+            version,
+            op,
+            length,
+            packets,
+        })
+    }
+}
+
+fn push(bits: &mut Vec<bool>, n: u64, len: usize) {
+    assert!(len == 64 || n >> len == 0, "{} doesn't fit in {} bits", n, len);
+    bits.extend((0..len).rev().map(|i| n >> i & 1 == 1));
+}
+
+impl Packet {
+    /// Decode the outermost packet of a hex transmission, the zero bits padding it out are
+    /// ignored.
+    pub fn decode(hex: &str) -> Result<Packet, DecodeError> {
+        let mut bits = Vec::new();
+        for c in hex.trim().chars() {
+            let nibble = c.to_digit(16).ok_or(DecodeError::Hex(c))?;
+            push(&mut bits, nibble as u64, 4);
+        }
+        Reader { bits: &bits, pos: 0 }.packet()
+    }
+
+    /// Hex transmission of the packet, padded with zeros to whole bytes. Fails on fields too
+    /// large for their bits.
+    pub fn encode(&self) -> Result<String, EncodeError> {
+        let mut bits = Vec::new();
+        self.write(&mut bits)?;
+        bits.resize(bits.len().div_ceil(8) * 8, false);
+        Ok(bits
+            .chunks(4)
+            .map(|nibble| nibble.iter().fold(0, |n, &bit| n << 1 | bit as u32))
+            .map(|n| std::char::from_digit(n, 16).unwrap().to_ascii_uppercase())
+            .collect())
+    }
+
+    fn write(&self, bits: &mut Vec<bool>) -> Result<(), EncodeError> {
+        let version = match self {
+            Packet::Literal { version, .. } | Packet::Operator { version, .. } => *version,
+        };
+        if version > 7 {
+            return Err(EncodeError::Version(version));
+        }
+
+        match self {
+            Packet::Literal { version, value } => {
+                push(bits, *version as u64, 3);
+                push(bits, 4, 3);
+                let groups = (64 - value.leading_zeros() as usize).div_ceil(4);
+                for i in (0..groups.max(1)).rev() {
+                    push(bits, (i > 0) as u64, 1);
+                    push(bits, value >> (4 * i) & 0xF, 4);
+                }
+            }
+            Packet::Operator {
+                version,
+                op,
+                length,
+                packets,
+            } => {
+                push(bits, *version as u64, 3);
+                push(bits, op.type_id(), 3);
+                match length {
+                    Length::Bits => {
+                        let mut sub = Vec::new();
+                        for p in packets {
+                            p.write(&mut sub)?;
+                        }
+                        if sub.len() >= 1 << 15 {
+                            return Err(EncodeError::Length(sub.len()));
+                        }
+                        push(bits, 0, 1);
+                        push(bits, sub.len() as u64, 15);
+                        bits.extend(sub);
+                    }
+                    Length::Count => {
+                        if packets.len() >= 1 << 11 {
+                            return Err(EncodeError::Count(packets.len()));
+                        }
+                        push(bits, 1, 1);
+                        push(bits, packets.len() as u64, 11);
+                        for p in packets {
+                            p.write(bits)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn version_sum(&self) -> u64 {
+        match self {
+            Packet::Literal { version, .. } => *version as u64,
+            Packet::Operator {
+                version, packets, ..
+            } => *version as u64 + packets.iter().map(Packet::version_sum).sum::<u64>(),
+        }
+    }
+
+    pub fn eval(&self) -> u64 {
+        match self {
+            Packet::Literal { value, .. } => *value,
+            Packet::Operator { op, packets, .. } => {
+                let mut values = packets.iter().map(Packet::eval);
+                match op {
+                    Op::Sum => values.sum(),
+                    Op::Product => values.product(),
+                    Op::Minimum => values.min().unwrap(),
+                    Op::Maximum => values.max().unwrap(),
+                    Op::Greater => (values.next() > values.next()) as u64,
+                    Op::Less => (values.next() < values.next()) as u64,
+                    Op::Equal => (values.next() == values.next()) as u64,
+                }
+            }
+        }
+    }
+
+    fn pretty(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        match self {
+            Packet::Literal { value, .. } => write!(f, "{}", value),
+            Packet::Operator { op, packets, .. } => {
+                write!(f, "({}", op.name())?;
+                // Nested operators each go on their own line in the alternate form
+                let nested = f.alternate()
+                    && packets.iter().any(|p| matches!(p, Packet::Operator { .. }));
+                for p in packets {
+                    if nested {
+                        write!(f, "\n{:1$}", "", indent + 2)?;
+                    } else {
+                        write!(f, " ")?;
+                    }
+                    p.pretty(f, indent + 2)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// S-expression of the packet, `{:#}` breaks nested operators over indented lines.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.pretty(f, 0)
+    }
+}
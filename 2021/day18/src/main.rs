@@ -1,11 +1,15 @@
+mod snailfish;
+
+use snailfish::{ParseError, Snailfish, Step};
 use std::fs::File;
 use std::io::prelude::*;
 use std::result::Result;
-use std::fmt::{Debug, Display};
+use std::fmt::Debug;
 
 #[derive(Debug)]
 enum AppErr {
     IoError(std::io::Error),
+    ParseError(ParseError),
 }
 
 impl From<std::io::Error> for AppErr {
@@ -14,6 +18,12 @@ impl From<std::io::Error> for AppErr {
     }
 }
 
+impl From<ParseError> for AppErr {
+    fn from(err: ParseError) -> Self {
+        Self::ParseError(err)
+    }
+}
+
 fn read_file(path: &str) -> Result<String, AppErr> {
     let mut file = File::open(path)?;
     let mut contents = String::new();
@@ -21,52 +31,17 @@ fn read_file(path: &str) -> Result<String, AppErr> {
     Ok(contents)
 }
 
-#[derive(Debug, Clone)]
-enum Num {
-    N (u32),
-    P (Box<Num>, Box<Num>)
-}
-
-impl Display for Num {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Num::N(i) => write!(f, "{}", i),
-            Num::P(a, b) => write!(f, "[{},{}]", a, b)
-        }
-    }
-}
-
-type Input = Vec<Num>;
+type Input = Vec<Snailfish>;
 
 fn parse(input: &str) -> Result<Input, AppErr> {
-
-    fn p(iter: &mut dyn Iterator<Item=char>) -> Num {
-
-        let c = iter.next().unwrap();
-        match c {
-            '[' => {
-                let a = p(iter);
-                assert!(iter.next() == Some(',') );
-                let b = p(iter);
-                assert!(iter.next() == Some(']') );
-
-                Num::P(Box::new(a), Box::new(b))
-            },
-            '0'..='9' => {
-                Num::N(c.to_digit(10).unwrap())
-            },
-            _ => panic!(),
-        }
-    }
-
-    Ok(input.lines().map(|line| p(&mut line.chars()) ).collect::<Vec<Num>>())
+    Ok(input.lines().map(|line| line.parse()).collect::<Result<_, _>>()?)
 }
 
 fn main() -> Result<(), AppErr> {
 
     fn explode_test(input: &str, result: &str) {
-        let mut r = parse(input).unwrap()[0].clone();
-        reduce_step(&mut r);
+        let mut r = input.parse::<Snailfish>().unwrap();
+        r.step();
         assert_eq!(r.to_string(), result);
     }
 
@@ -77,8 +52,7 @@ fn main() -> Result<(), AppErr> {
     explode_test("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[7,0]]]]");
   
     fn magnitude_test(input: &str, result: u32)  {
-        let r = &parse(input).unwrap()[0];
-        let m = magnitude( r );
+        let m = input.parse::<Snailfish>().unwrap().magnitude();
         assert_eq!(m, result);
     }
 
@@ -88,35 +62,48 @@ fn main() -> Result<(), AppErr> {
     magnitude_test("[[[[5,0],[7,4]],[5,5]],[6,6]]", 1137);
     magnitude_test("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]", 3488);
 
-
-    let a = parse("[[[[4,3],4],4],[7,[[8,4],9]]]")?.into_iter().next().unwrap();
-    let b = parse("[1,1]")?.into_iter().next().unwrap();
-    let mut c = addition(a, b);
-
-    assert_eq!(c.to_string(), "[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
-    reduce_step(&mut c);
-    assert_eq!(c.to_string(), "[[[[0,7],4],[7,[[8,4],9]]],[1,1]]");
-    reduce_step(&mut c);
-    assert_eq!(c.to_string(), "[[[[0,7],4],[15,[0,13]]],[1,1]]");
-    reduce_step(&mut c);
-    assert_eq!(c.to_string(), "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]");
-    reduce_step(&mut c);
-    assert_eq!(c.to_string(), "[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]");
-    reduce_step(&mut c);
+    assert!("[1,2".parse::<Snailfish>().is_err());
+    assert!("[1,2]3".parse::<Snailfish>().is_err());
+    assert!("[[1,2],3,4]".parse::<Snailfish>().is_err());
+    assert!("[[[[[[1,2],3],4],5],6],7]".parse::<Snailfish>().is_err());
+    assert!(matches!(parse("[1,1]\n[2"), Err(AppErr::ParseError(ParseError(msg))) if msg == "Incomplete number in \"[2\""));
+
+    let a = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse::<Snailfish>()?;
+    let b = "[1,1]".parse::<Snailfish>()?;
+    let mut trace = Vec::new();
+    let c = a.add_traced(b, |step, num| trace.push(format!("{}: {}", step, num)));
+
+    assert_eq!(trace, [
+        "explode [4,3]: [[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+        "explode [8,4]: [[[[0,7],4],[15,[0,13]]],[1,1]]",
+        "split 15: [[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+        "split 13: [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+        "explode [6,7]: [[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+    ]);
     assert_eq!(c.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
 
 
-    assert_eq!( fold(parse("[[[[4,3],4],4],[7,[[8,4],9]]]\n[1,1]")?).to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
-    assert_eq!( fold(parse("[1,1]\n[2,2]\n[3,3]\n[4,4]\n[5,5]\n[6,6]")?).to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+    assert_eq!( parse("[[[[4,3],4],4],[7,[[8,4],9]]]\n[1,1]")?.into_iter().sum::<Snailfish>().to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+    assert_eq!( parse("[1,1]\n[2,2]\n[3,3]\n[4,4]\n[5,5]\n[6,6]")?.into_iter().sum::<Snailfish>().to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
 
-    assert_eq!( fold(parse("[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]\n[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]")?).to_string(), "[[[[6,7],[6,7]],[[7,7],[0,7]]],[[[8,7],[7,7]],[[8,8],[8,0]]]]");
+    assert_eq!( parse("[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]\n[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]")?.into_iter().sum::<Snailfish>().to_string(), "[[[[6,7],[6,7]],[[7,7],[0,7]]],[[[8,7],[7,7]],[[8,8],[8,0]]]]");
 
     let test2 = parse(&read_file("test2.txt")?)?;
-    assert_eq!(fold(test2).to_string(), "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]");
+    assert_eq!(test2.into_iter().sum::<Snailfish>().to_string(), "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]");
 
     let test = parse(&read_file("test.txt")?)?;
     assert_eq!(part1(test.clone()), 4140);
 
+    // Show every reduction step of the example homework
+    if std::env::args().any(|arg| arg == "trace") {
+        let mut homework = test.iter().cloned();
+        let first = homework.next().unwrap();
+        homework.fold(first, |sum, num| {
+            println!("  {}\n+ {}", sum, num);
+            sum.add_traced(num, |step: Step, num: &Snailfish| println!("{:>16}: {}", step.to_string(), num))
+        });
+    }
+
     let input = parse(&read_file("input.txt")?)?;
     println!("{}", part1(input.clone()));
 
@@ -127,158 +114,8 @@ fn main() -> Result<(), AppErr> {
     Ok(())
 }
 
-fn magnitude(num: &Num) -> u32 {
-    match num {
-        Num::N(n) => *n,
-        Num::P(a, b) => 3 * magnitude(a) + 2 * magnitude(b)
-    }
-}
-
-fn addition(a: Num, b: Num) -> Num {
-    Num::P(Box::new(a), Box::new(b))
-}
-
-#[derive(Eq, PartialEq, Debug)]
-enum Reduce {
-    Done,
-    Changed,
-    Explode(Option<u32>, Option<u32>)
-}
-
-fn reduce_step_explode(num: &mut Num, depth: u32) -> Reduce {
-
-    match num {
-        Num::N(_) => {
-            Reduce::Done
-        },
-        Num::P(a,b) if depth >= 4 => {
-            
-            let a = match a.as_ref() {
-                Num::N(i) => *i,
-                _ => panic!()
-            };
-
-            let b = match b.as_ref() {
-                Num::N(i) => *i,
-                _ => panic!()
-            };
-
-            *num = Num::N(0);
-
-            Reduce::Explode(Some(a), Some(b))
-        },
-        Num::P(a,b) => {
-            let depth = depth + 1;
-            let r = reduce_step_explode(a, depth);
-            match r {
-                //Reduce::Changed => return Reduce::Changed,
-                Reduce::Done => (),
-                Reduce::Explode(p, Some(q)) => {
-
-                    fn add(num: &mut Num, s: u32) {
-                        match num {
-                            Num::N(i)   => *num = Num::N(*i + s),
-                            Num::P(a,_) => add(a, s),
-                        }
-                    }
-
-                    add(b, q);
-
-                    return Reduce::Explode(p, None)
-                },
-                _ => return r
-            }
-
-            let r = reduce_step_explode(b, depth);
-            match r {
-                //Reduce::Changed => return Reduce::Changed,
-                Reduce::Done => (),
-                Reduce::Explode(Some(p), q) => {
-
-                    fn add(num: &mut Num, s: u32) {
-                        match num {
-                            Num::N(i)   => *num = Num::N(*i + s),
-                            Num::P(_,b) => add(b, s),
-                        }
-                    }
-
-                    add(a, p);
-
-                    return Reduce::Explode(None, q)
-                },
-                _ => return r
-            }
-
-            Reduce::Done
-        }
-    }
-}
-
-fn reduce_step_split(num: &mut Num, depth: u32) -> Reduce {
-
-    match num {
-        Num::N(n) if *n > 9 => {
-            *num = Num::P( Box::new(Num::N(*n/2)), Box::new(Num::N((*n+1)/2)) );
-            Reduce::Changed
-        },
-        Num::N(_) => {
-            Reduce::Done
-        },
-        Num::P(_,_) if depth >= 4 => {
-            panic!()
-        },
-        Num::P(a,b) => {
-            let depth = depth + 1;
-            let r = reduce_step_split(a, depth);
-            match r {
-                Reduce::Done => (),
-                _ => return r
-            }
-
-            let r = reduce_step_split(b, depth);
-            match r {
-                Reduce::Done => (),
-                _ => return r
-            }
-
-            Reduce::Done
-        }
-    }
-}
-
-fn reduce_step(num: &mut Num) -> Reduce {
-    let e = reduce_step_explode(num, 0);
-    if e != Reduce::Done {
-        return e;
-    }
-
-    reduce_step_split(num, 0)
-}
-
-fn reduce(num: &mut Num) {
-    loop {
-        let r = reduce_step(num);
-        if r == Reduce::Done {
-            break
-        }
-    }
-}
-
-fn fold(input: Input) -> Num {
-    let mut iter = input.into_iter();
-    let mut sum = iter.next().unwrap();
-
-    for i in iter {
-        sum = addition(sum, i);
-        reduce(&mut sum);
-    }
-
-    sum
-}
-
 fn part1(input: Input) -> u32 {
-    let r = fold(input);
-    magnitude(&r)
+    input.into_iter().sum::<Snailfish>().magnitude()
 }
 
 fn part2(input: Input) -> u32 {
@@ -287,8 +124,7 @@ fn part2(input: Input) -> u32 {
     for a in 0..input.len() {
         for b in 0..input.len() {
             if a != b {
-                let v = vec![input[a].clone(), input[b].clone()];
-                let m = part1(v);
+                let m = (input[a].clone() + input[b].clone()).magnitude();
                 r.push(m);
             }
         }
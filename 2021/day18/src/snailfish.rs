@@ -0,0 +1,186 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+
+/// A snailfish number, stored flat as its regular numbers from left to right along with how
+/// many pairs each one is nested in. The pairs follow from the depths, so reducing only has to
+/// look at neighbours in the list.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Snailfish {
+    values: Vec<(u32, u8)>,
+}
+
+/// One action while reducing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Explode(u32, u32),
+    Split(u32),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::Explode(a, b) => write!(f, "explode [{},{}]", a, b),
+            Step::Split(n) => write!(f, "split {}", n),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError(pub String);
+
+impl Snailfish {
+    /// Apply the first reduction action, exploding before splitting, if there is one.
+    pub fn step(&mut self) -> Option<Step> {
+        // Numbers only go one pair too deep, so the first deep value starts a pair of two
+        if let Some(i) = self.values.iter().position(|&(_, depth)| depth > 4) {
+            let (a, b) = (self.values[i].0, self.values[i + 1].0);
+            if i > 0 {
+                self.values[i - 1].0 += a;
+            }
+            if let Some(next) = self.values.get_mut(i + 2) {
+                next.0 += b;
+            }
+            self.values.splice(i..i + 2, [(0, 4)]);
+            return Some(Step::Explode(a, b));
+        }
+
+        let i = self.values.iter().position(|&(n, _)| n > 9)?;
+        let (n, depth) = self.values[i];
+        self.values
+            .splice(i..=i, [(n / 2, depth + 1), (n.div_ceil(2), depth + 1)]);
+        Some(Step::Split(n))
+    }
+
+    pub fn reduce(&mut self) {
+        while self.step().is_some() {}
+    }
+
+    /// Add and reduce, calling `log` with every action and the number it left behind.
+    pub fn add_traced(mut self, mut rhs: Self, mut log: impl FnMut(Step, &Self)) -> Self {
+        if self.values.is_empty() || rhs.values.is_empty() {
+            self.values.append(&mut rhs.values);
+            return self;
+        }
+
+        self.reduce();
+        rhs.reduce();
+        self.values.append(&mut rhs.values);
+        for (_, depth) in &mut self.values {
+            *depth += 1;
+        }
+        while let Some(step) = self.step() {
+            log(step, &self);
+        }
+        self
+    }
+
+    pub fn magnitude(&self) -> u32 {
+        if self.values.is_empty() {
+            return 0;
+        }
+        let mut pos = 0;
+        self.walk(&mut pos, 0, &mut |n| n, &mut |a, b| 3 * a + 2 * b)
+    }
+
+    // Rebuild the tree below `depth` starting at `pos`, combining values bottom up
+    fn walk<T>(
+        &self,
+        pos: &mut usize,
+        depth: u8,
+        leaf: &mut impl FnMut(u32) -> T,
+        pair: &mut impl FnMut(T, T) -> T,
+    ) -> T {
+        let (n, d) = self.values[*pos];
+        if d == depth {
+            *pos += 1;
+            return leaf(n);
+        }
+        let a = self.walk(pos, depth + 1, leaf, pair);
+        let b = self.walk(pos, depth + 1, leaf, pair);
+        pair(a, b)
+    }
+}
+
+impl FromStr for Snailfish {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let err = |msg: &str| ParseError(format!("{} in {:?}", msg, s));
+
+        let mut values = Vec::new();
+        let mut depth = 0;
+        // For each open pair, how far along it is: 0 before the first element, 1 after it, 2 after
+        // the comma and 3 after the second element
+        let mut open = Vec::new();
+        let mut chars = s.trim().chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '[' if matches!(open.last(), None | Some(&0) | Some(&2)) => {
+                    depth += 1;
+                    if depth > 5 {
+                        return Err(err("Pairs nested too deep"));
+                    }
+                    open.push(0);
+                }
+                ',' if open.last() == Some(&1) => *open.last_mut().unwrap() = 2,
+                ']' if open.last() == Some(&3) => {
+                    open.pop();
+                    depth -= 1;
+                    if let Some(parts) = open.last_mut() {
+                        *parts += 1;
+                    }
+                }
+                '0'..='9' if matches!(open.last(), None | Some(&0) | Some(&2)) => {
+                    let mut n = c.to_digit(10).unwrap();
+                    while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        n = n * 10 + d;
+                        chars.next();
+                    }
+                    values.push((n, depth));
+                    if let Some(parts) = open.last_mut() {
+                        *parts += 1;
+                    }
+                }
+                _ => return Err(err(&format!("Unexpected {:?}", c))),
+            }
+            if open.is_empty() && chars.peek().is_some() {
+                return Err(err("Trailing characters"));
+            }
+        }
+
+        if values.is_empty() || !open.is_empty() {
+            return Err(err("Incomplete number"));
+        }
+        Ok(Snailfish { values })
+    }
+}
+
+impl fmt::Display for Snailfish {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.values.is_empty() {
+            return Ok(());
+        }
+        let mut pos = 0;
+        let s = self.walk(&mut pos, 0, &mut |n| n.to_string(), &mut |a, b| {
+            format!("[{},{}]", a, b)
+        });
+        write!(f, "{}", s)
+    }
+}
+
+impl Add for Snailfish {
+    type Output = Snailfish;
+
+    fn add(self, rhs: Snailfish) -> Snailfish {
+        self.add_traced(rhs, |_, _| ())
+    }
+}
+
+/// The sum of no numbers is the empty number, which adding to leaves the other side unchanged.
+impl Sum for Snailfish {
+    fn sum<I: Iterator<Item = Snailfish>>(iter: I) -> Snailfish {
+        iter.fold(Snailfish::default(), Add::add)
+    }
+}
@@ -0,0 +1,41 @@
+#![allow(dead_code)]
+
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    size: (i64, i64),
+    data: Vec<T>,
+}
+
+impl<T: Copy> Grid<T> {
+    pub fn with(x: i64, y: i64, v: T) -> Self {
+        Grid {
+            size: (x, y),
+            data: vec![v; (x * y) as usize],
+        }
+    }
+
+    pub fn size(&self) -> (i64, i64) {
+        self.size
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Option<T> {
+        if 0 <= x && x < self.size.0 && 0 <= y && y < self.size.1 {
+            Some(self.data[(self.size.0 * y + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut T> {
+        if 0 <= x && x < self.size.0 && 0 <= y && y < self.size.1 {
+            Some(&mut self.data[(self.size.0 * y + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (i64, i64, T)> + '_ {
+        (0..self.size.1)
+            .flat_map(move |y| (0..self.size.0).map(move |x| (x, y, self.get(x, y).unwrap())))
+    }
+}
@@ -2,6 +2,11 @@ use eyre::Result;
 use std::fs::File;
 use std::io::prelude::*;
 
+mod grid;
+mod ocr;
+use grid::Grid;
+use ocr::ocr_grid;
+
 fn read_file(path: &str) -> Result<String> {
     let mut file = File::open(path)?;
     let mut contents = String::new();
@@ -12,12 +17,73 @@ fn read_file(path: &str) -> Result<String> {
 type Input = Vec<Instr>;
 type Output = i32;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum Instr {
     Noop,
     Addx(i32),
 }
 
+impl Instr {
+    fn cycles(&self) -> usize {
+        match self {
+            Instr::Noop => 1,
+            Instr::Addx(_) => 2,
+        }
+    }
+
+    // Takes effect at the end of the instruction's last cycle
+    fn execute(&self, x: &mut i32) {
+        match self {
+            Instr::Noop => (),
+            Instr::Addx(v) => *x += v,
+        }
+    }
+}
+
+/// Runs a program one cycle at a time, yielding the cycle number, counted from 1, and the value
+/// of X during that cycle.
+struct Cpu<'a> {
+    program: std::slice::Iter<'a, Instr>,
+    // The instruction being executed and the cycles it still needs
+    current: Option<(Instr, usize)>,
+    cycle: i32,
+    x: i32,
+}
+
+impl<'a> Cpu<'a> {
+    fn new(program: &'a [Instr]) -> Self {
+        Cpu {
+            program: program.iter(),
+            current: None,
+            cycle: 0,
+            x: 1,
+        }
+    }
+}
+
+impl Iterator for Cpu<'_> {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<(i32, i32)> {
+        let (instr, left) = match self.current.take() {
+            Some(current) => current,
+            None => {
+                let instr = *self.program.next()?;
+                (instr, instr.cycles())
+            }
+        };
+
+        self.cycle += 1;
+        let x = self.x;
+        if left > 1 {
+            self.current = Some((instr, left - 1));
+        } else {
+            instr.execute(&mut self.x);
+        }
+        Some((self.cycle, x))
+    }
+}
+
 fn parse(input: &str) -> Result<Input> {
     Ok(input.lines().map(|l| {
         let mut parts = l.split_whitespace();
@@ -37,78 +103,43 @@ fn main() -> Result<()> {
     let input = parse(&read_file("input.txt")?)?;
     println!("part1: {:?}", part1(&input));
 
-    part2(&test);
-    part2(&input);
+    let crt = render(&test);
+    let row = (0..40).map(|x| if crt.get(x, 0).unwrap() { '#' } else { '.' }).collect::<String>();
+    assert!(row == "##..##..##..##..##..##..##..##..##..##..");
+    assert!(part2(&test).is_none());
+    println!("part2: {:?}", part2(&input));
+    assert!(part2(&input) == Some("PAPJCBHP".to_string()));
+
+    // Without its left edge the first P is no letter any more
+    let mut crt = render(&input);
+    for y in 0..6 {
+        *crt.get_mut(0, y).unwrap() = false;
+    }
+    assert!(ocr_grid(&crt).is_none());
 
     Ok(())
 }
 
 fn part1(input: &Input) -> Output {
-    let mut cycle = 0;
-    let mut reg_x = 1;
-    let mut sum = 0;
-
-    fn tick(cycle: &mut i32, sum: &mut i32, x: i32) {
-        *cycle += 1;
-        if *cycle >= 20 && (*cycle - 20) % 40 == 0 {
-            *sum += x * *cycle;
-        }
-    }
-
-    for instr in input {
-        match instr {
-            Instr::Noop => {
-                tick(&mut cycle, &mut sum, reg_x);
-            },
-            &Instr::Addx(v) => {
-                tick(&mut cycle, &mut sum, reg_x);
-                tick(&mut cycle, &mut sum, reg_x);
-                reg_x += v;
-            },
-        }
-
-        if cycle > 220 {
-            break
-        }
-    }
-
-    sum
+    Cpu::new(input)
+        .take_while(|&(cycle, _)| cycle <= 220)
+        .filter(|&(cycle, _)| (cycle - 20) % 40 == 0)
+        .map(|(cycle, x)| cycle * x)
+        .sum()
 }
 
-fn part2(input: &Input) {
-    let mut cycle = 0;
-    let mut reg_x = 1;
-
-    fn tick(cycle: &mut i32, x: i32) {
-        *cycle += 1;
-
-        let col = *cycle % 40;
-        let d = col - x;
-        if 0 <= d && d <= 2 {
-            print!("#");
-        } else {
-            print!(" ")
-        }
-
-        if (*cycle - 1) % 40 == 39 {
-            println!("");
+// The sprite is three pixels wide, centered on X, and the beam draws one pixel per cycle
+fn render(input: &Input) -> Grid<bool> {
+    let mut crt = Grid::with(40, 6, false);
+    for (cycle, x) in Cpu::new(input).take(240) {
+        let (col, row) = ((cycle - 1) % 40, (cycle - 1) / 40);
+        if (col - x).abs() <= 1 {
+            *crt.get_mut(col as i64, row as i64).unwrap() = true;
         }
     }
+    crt
+}
 
-    for instr in input {
-        match instr {
-            Instr::Noop => {
-                tick(&mut cycle, reg_x);
-            },
-            &Instr::Addx(v) => {
-                tick(&mut cycle, reg_x);
-                tick(&mut cycle, reg_x);
-                reg_x += v;
-            },
-        }
-
-        if cycle > 240 {
-            break
-        }
-    }
+fn part2(input: &Input) -> Option<String> {
+    ocr_grid(&render(input))
 }
@@ -0,0 +1,128 @@
+use crate::grid::Grid;
+use std::collections::HashSet;
+
+// The block letters Advent of Code draws answers with, 4x6 and 6x10 pixels
+#[rustfmt::skip]
+const SMALL: [(char, [&str; 6]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[rustfmt::skip]
+const LARGE: [(char, [&str; 10]); 15] = [
+    ('A', ["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', ["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', [".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', [".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', ["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', ["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', ["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', ["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', ["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', ["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', ["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', ["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', ["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+// Drop empty columns on both sides, glyphs are compared by their lit area only
+fn trim(rows: &[&str]) -> Vec<String> {
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let used = |x: usize| rows.iter().any(|r| r.as_bytes().get(x) == Some(&b'#'));
+    let start = (0..width).find(|&x| used(x)).unwrap_or(0);
+    let end = (0..width).rfind(|&x| used(x)).map_or(0, |x| x + 1);
+    rows.iter().map(|r| r[start..end].to_string()).collect()
+}
+
+fn lookup(glyph: &[String]) -> Option<char> {
+    match glyph.len() {
+        6 => SMALL
+            .iter()
+            .find(|(_, rows)| trim(rows) == glyph)
+            .map(|(c, _)| *c),
+        10 => LARGE
+            .iter()
+            .find(|(_, rows)| trim(rows) == glyph)
+            .map(|(c, _)| *c),
+        _ => None,
+    }
+}
+
+/// Read the letters drawn by the lit pixels, None when something isn't a known letter.
+pub fn ocr(points: impl IntoIterator<Item = (i64, i64)>) -> Option<String> {
+    let points: HashSet<(i64, i64)> = points.into_iter().collect();
+    let x0 = points.iter().map(|p| p.0).min()?;
+    let x1 = points.iter().map(|p| p.0).max()?;
+    let y0 = points.iter().map(|p| p.1).min()?;
+    let y1 = points.iter().map(|p| p.1).max()?;
+
+    let column = |x: i64| (y0..=y1).any(|y| points.contains(&(x, y)));
+
+    let mut text = String::new();
+    let mut x = x0;
+    while x <= x1 {
+        if !column(x) {
+            x += 1;
+            continue;
+        }
+
+        let start = x;
+        while x <= x1 && column(x) {
+            x += 1;
+        }
+
+        let glyph: Vec<String> = (y0..=y1)
+            .map(|y| {
+                (start..x)
+                    .map(|x| if points.contains(&(x, y)) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        text.push(lookup(&glyph)?);
+    }
+
+    Some(text)
+}
+
+/// Same as `ocr`, for the lit cells of a grid.
+pub fn ocr_grid(grid: &Grid<bool>) -> Option<String> {
+    ocr(grid
+        .iter()
+        .filter(|&(_, _, lit)| lit)
+        .map(|(x, y, _)| (x, y)))
+}
+
+#[test]
+fn test() {
+    // A large H drawn point by point: two bars of ten with a crossbar on the fifth row
+    let bars = (0..10).flat_map(|y| vec![(0, y), (5, y)]);
+    let crossbar = (1..5).map(|x| (x, 4));
+    assert!(ocr(bars.chain(crossbar)) == Some("H".to_string()));
+
+    let mut grid = Grid::with(6, 10, false);
+    for (x, y) in (0..10)
+        .flat_map(|y| vec![(0, y), (5, y)])
+        .chain((1..5).map(|x| (x, 4)))
+    {
+        *grid.get_mut(x, y).unwrap() = true;
+    }
+    assert!(ocr_grid(&grid) == Some("H".to_string()));
+}
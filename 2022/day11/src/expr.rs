@@ -0,0 +1,133 @@
+use eyre::{eyre, Result};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Arithmetic over `old` and integers, as found on the right of `new = ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Old,
+    Num(i64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Parse with the usual precedence, `*` and `/` before `+` and `-`, all left associative.
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut chars = input.chars().peekable();
+        let expr = sum(&mut chars)?;
+        skip_spaces(&mut chars);
+        match chars.next() {
+            None => Ok(expr),
+            Some(c) => Err(eyre!("Unexpected {:?} in {:?}", c, input)),
+        }
+    }
+
+    /// The value for `old`, None on a division by zero.
+    pub fn eval(&self, old: i64) -> Option<i64> {
+        Some(match self {
+            Expr::Old => old,
+            Expr::Num(n) => *n,
+            Expr::Add(a, b) => a.eval(old)? + b.eval(old)?,
+            Expr::Sub(a, b) => a.eval(old)? - b.eval(old)?,
+            Expr::Mul(a, b) => a.eval(old)? * b.eval(old)?,
+            Expr::Div(a, b) => a.eval(old)?.checked_div(b.eval(old)?)?,
+        })
+    }
+
+    /// Whether it doesn't divide, so the result modulo n only depends on `old` modulo n.
+    pub fn keeps_modulo(&self) -> bool {
+        match self {
+            Expr::Old | Expr::Num(_) => true,
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => {
+                a.keeps_modulo() && b.keeps_modulo()
+            }
+            Expr::Div(..) => false,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Old => write!(f, "old"),
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Sub(a, b) => write!(f, "({} - {})", a, b),
+            Expr::Mul(a, b) => write!(f, "({} * {})", a, b),
+            Expr::Div(a, b) => write!(f, "({} / {})", a, b),
+        }
+    }
+}
+
+fn skip_spaces(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn sum(chars: &mut Peekable<Chars>) -> Result<Expr> {
+    let mut expr = product(chars)?;
+    loop {
+        skip_spaces(chars);
+        if chars.next_if_eq(&'+').is_some() {
+            expr = Expr::Add(Box::new(expr), Box::new(product(chars)?));
+        } else if chars.next_if_eq(&'-').is_some() {
+            expr = Expr::Sub(Box::new(expr), Box::new(product(chars)?));
+        } else {
+            return Ok(expr);
+        }
+    }
+}
+
+fn product(chars: &mut Peekable<Chars>) -> Result<Expr> {
+    let mut expr = term(chars)?;
+    loop {
+        skip_spaces(chars);
+        if chars.next_if_eq(&'*').is_some() {
+            expr = Expr::Mul(Box::new(expr), Box::new(term(chars)?));
+        } else if chars.next_if_eq(&'/').is_some() {
+            let divisor = term(chars)?;
+            if divisor == Expr::Num(0) {
+                return Err(eyre!("Division by zero after {}", expr));
+            }
+            expr = Expr::Div(Box::new(expr), Box::new(divisor));
+        } else {
+            return Ok(expr);
+        }
+    }
+}
+
+fn term(chars: &mut Peekable<Chars>) -> Result<Expr> {
+    skip_spaces(chars);
+    match chars.peek() {
+        Some('(') => {
+            chars.next();
+            let expr = sum(chars)?;
+            skip_spaces(chars);
+            match chars.next() {
+                Some(')') => Ok(expr),
+                c => Err(eyre!("Expected ')' but found {:?}", c)),
+            }
+        }
+        Some(c) if c.is_ascii_digit() => {
+            let mut digits = String::new();
+            while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                digits.push(c);
+            }
+            Ok(Expr::Num(digits.parse()?))
+        }
+        Some(c) if c.is_alphabetic() => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric()) {
+                word.push(c);
+            }
+            match word.as_str() {
+                "old" => Ok(Expr::Old),
+                _ => Err(eyre!("Unknown variable {}", word)),
+            }
+        }
+        c => Err(eyre!("Expected a number, old or '(' but found {:?}", c)),
+    }
+}
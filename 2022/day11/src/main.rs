@@ -1,4 +1,7 @@
-use eyre::Result;
+mod expr;
+
+use expr::Expr;
+use eyre::{eyre, Result};
 use std::fs::File;
 use std::io::prelude::*;
 
@@ -15,7 +18,7 @@ type Output = i64;
 #[derive(Debug, Clone)]
 struct Monkey {
     items: Vec<i64>,
-    operation: fn(old: i64) -> i64,
+    operation: Expr,
     divisor: i64,
     if_true: usize,
     if_false: usize,
@@ -30,19 +33,7 @@ fn parse(input: &str) -> Result<Input> {
         assert!(line == format!("Monkey {}:", monkeys.len()), "{:?}", line.chars());
 
         let items = lines.next().unwrap().split([':', ',']).skip(1).map(|num| num.trim().parse().unwrap()).collect();
-        let operation = match lines.next().unwrap().split('=').last().unwrap().trim() {
-            "old * 19" => |x| x * 19,
-            "old + 6" => |x| x + 6,
-            "old * old" => |x| x * x,
-            "old + 3" => |x| x + 3,
-            "old * 17" => |x| x * 17,
-            "old + 1" => |x| x + 1,
-            "old + 2" => |x| x + 2,
-            "old + 7" => |x| x + 7,
-            "old + 8" => |x| x + 8,
-            "old * 2" => |x| x * 2,
-            x => panic!("No match for: {}", x),
-        };
+        let operation = Expr::parse(lines.next().unwrap().rsplit('=').next().unwrap())?;
 
         let divisor = lines.next().unwrap().split_whitespace().last().map(|num| num.parse().unwrap()).unwrap();
         let if_true = lines.next().unwrap().split_whitespace().last().map(|num| num.parse().unwrap()).unwrap();
@@ -63,30 +54,56 @@ fn parse(input: &str) -> Result<Input> {
 }
 
 fn main() -> Result<()> {
+    let expr = Expr::parse("(old + 2) * 3 - old / 2")?;
+    assert!(expr.eval(10) == Some(31));
+    assert!(Expr::parse("old / (old - 5)")?.eval(5).is_none());
+    assert!(Expr::parse("old / 0").is_err());
+    assert!(expr.to_string() == "(((old + 2) * 3) - (old / 2))");
+    assert!(Expr::parse("old * (3").is_err());
+    assert!(Expr::parse("new + 1").is_err());
+    assert!(Expr::parse("old * old - 3")?.keeps_modulo());
+    assert!(!expr.keeps_modulo());
+
     let test = parse(&read_file("test.txt")?)?;
     println!("{:?}", test);
-    assert!(dbg!(part1(&test)) == 10605);
+    assert!(dbg!(part1(&test)?) == 10605);
 
     let input = parse(&read_file("input.txt")?)?;
-    println!("part1: {:?}", part1(&input));
-
-    assert!(dbg!(part2(&test)) == 2713310158);
-    println!("part2: {:?}", part2(&input));
+    println!("part1: {:?}", part1(&input)?);
+    let mut dividing = test.clone();
+    dividing[0].operation = Expr::parse("old / (old - 79)")?;
+    assert!(part1(&dividing).is_err());
+
+    assert!(dbg!(part2(&test)?) == 2713310158);
+    let mut halving = test.clone();
+    halving[0].operation = Expr::parse("old / 2")?;
+    assert!(part2(&halving).is_err());
+    // Worry levels go negative, reducing by a multiple of the modulo has to agree
+    let mut subtracting = test.clone();
+    subtracting[3].operation = Expr::parse("old - 30")?;
+    let modulo: i64 = subtracting.iter().map(|m| m.divisor).product();
+    assert!(part2(&subtracting)? == simulate(&subtracting, 10000, |x| x.rem_euclid(7 * modulo))?);
+    println!("part2: {:?}", part2(&input)?);
 
     Ok(())
 }
 
-fn part1(input: &Input) -> Output {
+// Play the rounds, calming down after each inspection, and multiply the two highest counts of
+// inspected items
+fn simulate(input: &Input, rounds: usize, relief: impl Fn(i64) -> i64) -> Result<Output> {
     let mut inspected = vec![0; input.len()];
     let mut input = input.clone();
 
-    for _ in 1..=20 {
+    for _round in 1..=rounds {
         for i in 0..input.len() {
             let items = std::mem::take(&mut input[i].items);
             let monkey = input[i].clone();
             for item in items {
                 inspected[i] += 1;
-                let new = (monkey.operation)(item) / 3;
+                let new = monkey.operation.eval(item).ok_or_else(|| {
+                    eyre!("new = {} divides by zero for old = {}", monkey.operation, item)
+                })?;
+                let new = relief(new);
 
                 if new % monkey.divisor == 0 {
                     input[monkey.if_true].items.push(new);
@@ -98,32 +115,21 @@ fn part1(input: &Input) -> Output {
     }
 
     inspected.sort();
-    inspected.iter().rev().take(2).product()
+    Ok(inspected.iter().rev().take(2).product())
 }
 
-fn part2(input: &Input) -> Output {
-    let mut inspected = vec![0; input.len()];
-    let mut input = input.clone();
-
-    let modulo: i64 = dbg!(input.iter().map(|m| m.divisor).product());
-
-    for _round in 1..=10000 {
-        for i in 0..input.len() {
-            let items = std::mem::take(&mut input[i].items);
-            let monkey = input[i].clone();
-            for item in items {
-                inspected[i] += 1;
-                let new = (monkey.operation)(item) % modulo;
+fn part1(input: &Input) -> Result<Output> {
+    simulate(input, 20, |x| x / 3)
+}
 
-                if new % monkey.divisor == 0 {
-                    input[monkey.if_true].items.push(new);
-                } else {
-                    input[monkey.if_false].items.push(new);
-                }
-            }
-        }
+fn part2(input: &Input) -> Result<Output> {
+    if let Some(monkey) = input.iter().find(|m| !m.operation.keeps_modulo()) {
+        return Err(eyre!(
+            "Can't keep worry levels small with new = {}",
+            monkey.operation
+        ));
     }
+    let modulo: i64 = input.iter().map(|m| m.divisor).product();
 
-    inspected.sort();
-    inspected.iter().rev().take(2).product()
+    simulate(input, 10000, |x| x.rem_euclid(modulo))
 }
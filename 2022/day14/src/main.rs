@@ -1,3 +1,5 @@
+mod anim;
+
 use nom::{
     bytes::complete::tag,
     character::complete::digit1,
@@ -36,16 +38,35 @@ fn parse(input: &str) -> Input {
     data
 }
 
-fn main() {
+fn main() -> std::io::Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["play"] => return anim::play(frames(&parse(&read_file("input.txt"))), 30),
+        ["record", path] => return anim::record(frames(&parse(&read_file("input.txt"))), path),
+        ["replay", path] => return anim::play(anim::load(path)?, 30),
+        [] => (),
+        _ => {
+            return Err(std::io::Error::other(
+                "Usage: [play | record <file> | replay <file>]",
+            ))
+        }
+    }
+
     let test = parse(&read_file("test.txt"));
     println!("{:?}", test);
     assert!(dbg!(part1(&test)) == 24);
+    // The empty cave, then one frame per grain that comes to rest
+    let last = frames(&test).last().unwrap();
+    assert!(frames(&test).count() == 25);
+    assert!(last.matches('o').count() == 24);
 
     let input = parse(&read_file("input.txt"));
     println!("part1: {:?}", part1(&input));
 
     assert!(dbg!(part2(&test)) == 93);
     println!("part2: {:?}", part2(&input));
+
+    Ok(())
 }
 
 fn world(input: &Input) -> (HashSet<(i32, i32)>, i32) {
@@ -133,3 +154,39 @@ fn part2(input: &Input) -> Output {
 
     i
 }
+
+/// Part 1 one grain at a time: rock, resting sand and the source, cropped to the rock.
+fn frames(input: &Input) -> impl Iterator<Item = String> {
+    let (mut world, depth) = world(input);
+    let start = (500, 0);
+    let rock = world.clone();
+    let x0 = rock.iter().map(|p| p.0).min().unwrap_or(start.0) - 1;
+    let x1 = rock.iter().map(|p| p.0).max().unwrap_or(start.0) + 1;
+
+    let draw = move |world: &HashSet<(i32, i32)>| {
+        let mut frame = String::new();
+        for y in 0..=depth {
+            for x in x0..=x1 {
+                frame.push(if (x, y) == start {
+                    '+'
+                } else if rock.contains(&(x, y)) {
+                    '#'
+                } else if world.contains(&(x, y)) {
+                    'o'
+                } else {
+                    '.'
+                });
+            }
+            frame.push('\n');
+        }
+        frame
+    };
+
+    let first = draw(&world);
+    let grains = std::iter::from_fn(move || {
+        let next = move_sand(&world, depth, start).ok()?;
+        world.insert(next);
+        Some(draw(&world))
+    });
+    std::iter::once(first).chain(grains)
+}
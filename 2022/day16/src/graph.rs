@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+
+pub const UNREACHABLE: usize = usize::MAX;
+
+/// Distances between just the `keep` nodes, with a breadth first search from each of them.
+/// Row and column `i` of the result belong to `keep[i]`.
+pub fn compress(adj: &[Vec<usize>], keep: &[usize]) -> Vec<Vec<usize>> {
+    keep.iter()
+        .map(|&from| {
+            let mut dist = vec![UNREACHABLE; adj.len()];
+            let mut queue = VecDeque::from([from]);
+            dist[from] = 0;
+            while let Some(i) = queue.pop_front() {
+                for &j in &adj[i] {
+                    if dist[j] == UNREACHABLE {
+                        dist[j] = dist[i] + 1;
+                        queue.push_back(j);
+                    }
+                }
+            }
+            keep.iter().map(|&to| dist[to]).collect()
+        })
+        .collect()
+}
+
+/// Best total value for every subset of targets, when walking from `start` over the compressed
+/// distances and spending one more minute at each target. A target reached with `t` minutes to
+/// spare is worth `rate * t`. Targets are nodes `0..rates.len()`, subset bit `i` is target `i`.
+pub fn best_by_subset(
+    dist: &[Vec<usize>],
+    start: usize,
+    rates: &[usize],
+    budget: usize,
+) -> Vec<usize> {
+    fn visit(
+        dist: &[Vec<usize>],
+        rates: &[usize],
+        at: usize,
+        left: usize,
+        mask: usize,
+        value: usize,
+        best: &mut [usize],
+    ) {
+        best[mask] = best[mask].max(value);
+        for (j, &rate) in rates.iter().enumerate() {
+            let cost = dist[at][j].saturating_add(1);
+            if mask & (1 << j) != 0 || cost >= left {
+                continue;
+            }
+            let left = left - cost;
+            visit(
+                dist,
+                rates,
+                j,
+                left,
+                mask | (1 << j),
+                value + rate * left,
+                best,
+            );
+        }
+    }
+
+    let mut best = vec![0; 1 << rates.len()];
+    visit(dist, rates, start, budget, 0, 0, &mut best);
+    best
+}
+
+/// Best total of two agents that each cover a disjoint subset of targets.
+pub fn best_pair(best: &[usize]) -> usize {
+    // Spread every subset's value to its supersets, one bit at a time
+    let mut within = best.to_vec();
+    let full = within.len() - 1;
+    let mut bit = 1;
+    while bit <= full {
+        for mask in 0..=full {
+            if mask & bit != 0 {
+                within[mask] = within[mask].max(within[mask ^ bit]);
+            }
+        }
+        bit <<= 1;
+    }
+
+    (0..=full)
+        .map(|mask| best[mask] + within[full ^ mask])
+        .max()
+        .unwrap_or(0)
+}
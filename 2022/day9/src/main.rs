@@ -1,4 +1,6 @@
-use eyre::Result;
+mod anim;
+
+use eyre::{eyre, Result};
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::prelude::*;
@@ -23,6 +25,15 @@ fn parse(input: &str) -> Result<Input> {
 }
 
 fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["play"] => return Ok(anim::play(frames(&parse(&read_file("input.txt")?)?), 30)?),
+        ["record", path] => return Ok(anim::record(frames(&parse(&read_file("input.txt")?)?), path)?),
+        ["replay", path] => return Ok(anim::play(anim::load(path)?, 30)?),
+        [] => (),
+        _ => return Err(eyre!("Usage: [play | record <file> | replay <file>]")),
+    }
+
     let test = parse(&read_file("test.txt")?)?;
     println!("{:?}", test);
 
@@ -34,6 +45,10 @@ fn main() -> Result<()> {
     assert!(dbg!(part2(&test)) == 1);
     let test2 = parse(&read_file("test2.txt")?)?;
     assert!(dbg!(part2(&test2)) == 36);
+    // A frame before the first step and one after each, with the window centered on the head
+    assert!(frames(&test2).count() == 1 + test2.iter().map(|&(_, n)| n as usize).sum::<usize>());
+    let last = frames(&test2).last().unwrap();
+    assert!(last.lines().nth(10).and_then(|row| row.chars().nth(20)) == Some('H'));
 
     println!("part2: {:?}", part2(&input));
 
@@ -119,6 +134,21 @@ fn pull(head: Pos, tail: &mut Pos) {
     }
 }
 
+// Move the head one step and let the other knots follow
+fn step(rope: &mut [Pos], action: char) {
+    match action {
+        'U' => rope[0].y += 1,
+        'D' => rope[0].y -= 1,
+        'R' => rope[0].x += 1,
+        'L' => rope[0].x -= 1,
+        _ => panic!(),
+    }
+
+    for i in 1..rope.len() {
+        pull(rope[i - 1], &mut rope[i]);
+    }
+}
+
 fn part2(input: &Input) -> Output {
     let mut trail = HashSet::<Pos>::new();
     let mut rope = [Pos::default(); 10];
@@ -127,21 +157,47 @@ fn part2(input: &Input) -> Output {
 
     for &(action, count) in input {
         for _ in 0..count {
-            match action {
-                'U' => rope[0].y += 1,
-                'D' => rope[0].y -= 1,
-                'R' => rope[0].x += 1,
-                'L' => rope[0].x -= 1,
-                _ => panic!(),
-            }
-
-            for i in 1..rope.len() {
-                pull(rope[i - 1], &mut rope[i]);
-            }
-
+            step(&mut rope, action);
             trail.insert(*rope.last().unwrap());
         }
     }
 
     trail.len()
 }
+
+// The part 2 rope in a window following the head: H is the head, the other knots are numbered
+// from it, # marks where the tail has been and s the start
+fn draw(rope: &[Pos], trail: &HashSet<Pos>) -> String {
+    let head = rope[0];
+    let mut frame = String::new();
+    for y in (head.y - 10..=head.y + 10).rev() {
+        for x in head.x - 20..=head.x + 20 {
+            let pos = Pos { x, y };
+            frame.push(match rope.iter().position(|&knot| knot == pos) {
+                Some(0) => 'H',
+                Some(i) => char::from_digit(i as u32, 10).unwrap(),
+                None if pos == Pos::default() => 's',
+                None if trail.contains(&pos) => '#',
+                None => '.',
+            });
+        }
+        frame.push('\n');
+    }
+    frame
+}
+
+/// The rope before the first step and after every step.
+fn frames(input: &Input) -> impl Iterator<Item = String> + '_ {
+    let mut rope = [Pos::default(); 10];
+    let mut trail = HashSet::from([rope[9]]);
+    let first = draw(&rope, &trail);
+
+    let actions = input
+        .iter()
+        .flat_map(|&(action, count)| std::iter::repeat_n(action, count as usize));
+    std::iter::once(first).chain(actions.map(move |action| {
+        step(&mut rope, action);
+        trail.insert(rope[9]);
+        draw(&rope, &trail)
+    }))
+}
@@ -0,0 +1,104 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A sequence of states that repeats after `start` steps with the given `period`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The earliest step that has the same state as step `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+
+    /// State after `n` steps, only simulating up to the first repetition.
+    pub fn nth<T>(&self, initial: T, mut step: impl FnMut(&T) -> T, n: usize) -> T {
+        (0..self.reduce(n)).fold(initial, |state, _| step(&state))
+    }
+}
+
+/// Brent's algorithm, needs only a couple of states in memory at a time.
+pub fn find_cycle<T: Clone + Eq>(initial: T, mut step: impl FnMut(&T) -> T) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = (0..period).fold(initial, |state, _| step(&state));
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Remember every state until one repeats. Steps each state once and also returns the states
+/// seen, so values up to the repetition can be looked up without simulating again.
+pub fn find_cycle_hashed<T: Clone + Eq + Hash>(
+    initial: T,
+    mut step: impl FnMut(&T) -> T,
+) -> (Cycle, Vec<T>) {
+    let mut seen = HashMap::new();
+    let mut history = vec![];
+    let mut state = initial;
+    loop {
+        if let Some(&start) = seen.get(&state) {
+            let period = history.len() - start;
+            return (Cycle { start, period }, history);
+        }
+        seen.insert(state.clone(), history.len());
+        let next = step(&state);
+        history.push(state);
+        state = next;
+    }
+}
+
+/// State after `n` steps of a sequence that eventually repeats.
+pub fn nth<T: Clone + Eq + Hash>(initial: T, step: impl FnMut(&T) -> T, n: usize) -> T {
+    let (cycle, mut history) = find_cycle_hashed(initial, step);
+    history.swap_remove(cycle.reduce(n))
+}
+
+#[test]
+fn test() {
+    // 2, 4, 16, 256 % 97 = 62, 62^2 % 97 = 61, ...
+    let step = |x: &u64| x * x % 97;
+    let (cycle, history) = find_cycle_hashed(2, step);
+    assert_eq!(find_cycle(2, step), cycle);
+    assert_eq!(history.len(), cycle.start + cycle.period);
+
+    for n in 0..100 {
+        let expected = (0..n).fold(2, |x, _| step(&x));
+        assert_eq!(nth(2, step, n), expected);
+        assert_eq!(cycle.nth(2, step, n), expected);
+    }
+
+    let tail = Cycle {
+        start: 3,
+        period: 4,
+    };
+    assert_eq!(tail.reduce(2), 2);
+    assert_eq!(tail.reduce(1_000_000_000), 3 + (1_000_000_000 - 3) % 4);
+}
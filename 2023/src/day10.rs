@@ -1,5 +1,6 @@
 use eyre::Result;
-use std::collections::HashMap;
+
+use crate::polygon::Polygon;
 
 type Input = Vec<Vec<char>>;
 type Output = i64;
@@ -65,14 +66,6 @@ pub fn part1(input: &Input) -> Output {
     lenght / 2
 }
 
-fn horizontal(a: (i64, i64), b: (i64, i64)) -> i64 {
-    match (a, b) {
-        (_, (0, 1)) => 1,
-        ((0, -1), _) => -1,
-        _ => 0,
-    }
-}
-
 pub fn part2(input: &Input) -> Output {
     let mut pos = input
         .iter()
@@ -93,36 +86,18 @@ pub fn part2(input: &Input) -> Output {
         .copied()
         .unwrap();
 
-    let start_dir = dir;
-    let start_pos = pos;
-    let mut path = HashMap::<(i64, i64), i64>::new();
+    let mut path = vec![pos];
     loop {
         pos = (pos.0 + dir.0, pos.1 + dir.1);
         let neighbor = get(input, pos.0, pos.1).unwrap_or('.');
         if neighbor == 'S' {
             break;
         }
-        let next = connected(dir, neighbor).unwrap();
-        path.insert(pos, horizontal(dir, next));
-        dir = next;
+        dir = connected(dir, neighbor).unwrap();
+        path.push(pos);
     }
-    path.insert(start_pos, horizontal(dir, start_dir));
-    let path = &path;
-
-    let height = input.len() as i64;
-    let width = input[0].len() as i64;
-    (0..height)
-        .flat_map(|y| {
-            (0..width).scan(0, move |level, x| {
-                let dy = path.get(&(x, y)).copied();
-                if let Some(dy) = dy {
-                    *level += dy;
-                }
-                Some(dy.is_none() && *level != 0)
-            })
-        })
-        .filter(|p| *p)
-        .count() as i64
+
+    Polygon::new(path).interior_points()
 }
 
 #[test]
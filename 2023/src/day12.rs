@@ -1,5 +1,6 @@
 use eyre::Result;
-use std::collections::HashMap;
+
+use crate::memo::Memo;
 
 type Input = Vec<(Vec<char>, Vec<i64>)>;
 type Output = i64;
@@ -42,7 +43,7 @@ pub fn part2(input: &Input) -> Output {
 }
 
 fn solve_inner<'a>(
-    cache: &mut HashMap<(&'a [char], &'a [i64]), i64>,
+    memo: &mut Memo<(&'a [char], &'a [i64]), i64>,
     pattern: &'a [char],
     groups: &'a [i64],
 ) -> Output {
@@ -100,11 +101,11 @@ fn solve_inner<'a>(
             && pattern[i + g1 + 1..][..g2].iter().all(|c| *c != '.')
         {
             // TODO Combine permutation calc with check for early exit
-            let permutations = solve_inner(cache, &pattern[..i + g1], &groups[..=0]);
+            let permutations = solve_inner(memo, &pattern[..i + g1], &groups[..=0]);
 
             if permutations != 0 {
                 let pattern_rem = pattern.get(i + g1 + 1 + g2 + 1..).unwrap_or_default();
-                sum += permutations * solve_cached(cache, pattern_rem, &groups[2..]);
+                sum += permutations * solve_cached(memo, pattern_rem, &groups[2..]);
             }
         }
     }
@@ -115,22 +116,15 @@ fn solve_inner<'a>(
 // The cache allows to reuse calculation
 // It deduplicates sub-calculations in the the recursive calculation tree.
 fn solve_cached<'a>(
-    cache: &mut HashMap<(&'a [char], &'a [i64]), i64>,
+    memo: &mut Memo<(&'a [char], &'a [i64]), i64>,
     pattern: &'a [char],
     groups: &'a [i64],
 ) -> Output {
-    if let Some(hit) = cache.get(&(pattern, groups)) {
-        return *hit;
-    }
-
-    let ret = solve_inner(cache, pattern, groups);
-    cache.insert((pattern, groups), ret);
-    ret
+    memo.get((pattern, groups), |memo| solve_inner(memo, pattern, groups))
 }
 
 fn solve(pattern: &[char], groups: &[i64]) -> Output {
-    let mut cache = HashMap::new();
-    solve_cached(&mut cache, pattern, groups)
+    solve_cached(&mut Memo::new(), pattern, groups)
 }
 
 #[test]
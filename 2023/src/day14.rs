@@ -1,5 +1,6 @@
 use eyre::Result;
-use std::collections::HashMap;
+
+use crate::cycle;
 
 type Input = Vec<Vec<char>>;
 type Output = i64;
@@ -96,28 +97,16 @@ pub fn part1(input: &Input) -> Output {
 }
 
 pub fn part2(input: &Input) -> Output {
-    let mut grid = input.clone();
-
-    let mut history = HashMap::new();
-    const N: i64 = 1000000000;
-    for i in 1..=N {
+    let spin = |grid: &Input| {
+        let mut grid = grid.clone();
         tilt(&mut grid, Dir::North);
         tilt(&mut grid, Dir::West);
         tilt(&mut grid, Dir::South);
         tilt(&mut grid, Dir::East);
+        grid
+    };
 
-        if let Some(prev) = history.get(&grid) {
-            let cycle = i - prev;
-            let rem = N - i;
-            if rem % cycle == 0 {
-                break;
-            }
-        }
-
-        history.insert(grid.clone(), i);
-    }
-
-    load(&grid)
+    load(&cycle::nth(input.clone(), spin, 1000000000))
 }
 
 #[test]
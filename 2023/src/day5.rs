@@ -1,5 +1,6 @@
 use eyre::Result;
-use std::cmp::{max, min};
+
+use crate::interval::{IntervalMap, IntervalSet};
 
 type Input = (Vec<i64>, Vec<Vec<Vec<i64>>>);
 type Output = i64;
@@ -43,63 +44,29 @@ pub fn parse(input: &str) -> Result<Input> {
     Ok((seeds, maps))
 }
 
+fn almanac(maps: &[Vec<Vec<i64>>]) -> IntervalMap {
+    maps.iter().fold(IntervalMap::new(), |almanac, m| {
+        let mut map = IntervalMap::new();
+        for n in m {
+            map.insert(n[1]..n[1] + n[2], n[0] - n[1]);
+        }
+        almanac.then(&map)
+    })
+}
+
 pub fn part1(input: &Input) -> Output {
-    input
-        .0
-        .iter()
-        .map(|s| {
-            input.1.iter().fold(*s, |v, m| {
-                m.iter()
-                    .find(|n| n[1] <= v && v < n[1] + n[2])
-                    .map(|n| n[0] + v - n[1])
-                    .unwrap_or(v)
-            })
-        })
-        .min()
-        .unwrap()
+    let almanac = almanac(&input.1);
+    input.0.iter().map(|s| almanac.get(*s)).min().unwrap()
 }
 
 pub fn part2(input: &Input) -> Output {
-    let ranges = input
+    let seeds = input
         .0
         .chunks_exact(2)
-        .map(|pair| (pair[0], pair[0] + pair[1]))
-        .collect::<Vec<_>>();
+        .map(|pair| pair[0]..pair[0] + pair[1])
+        .collect::<IntervalSet>();
 
-    input
-        .1
-        .iter()
-        .fold(ranges, |ranges, m| {
-            let mut new = vec![];
-            let rem = m.iter().fold(ranges, |ranges, n| {
-                let begin = n[1];
-                let end = n[1] + n[2];
-                let mut rem = vec![];
-                for pair in ranges {
-                    if pair.0 < begin {
-                        rem.push((pair.0, min(pair.1, begin)));
-                    }
-
-                    if pair.1 > end {
-                        rem.push((max(end, pair.0), pair.1));
-                    }
-
-                    let a = max(pair.0, begin);
-                    let b = min(pair.1, end);
-                    if b - a > 1 {
-                        let shift = n[0] - begin;
-                        new.push((a + shift, b + shift));
-                    }
-                }
-                rem
-            });
-            new.extend(rem.iter());
-            new
-        })
-        .iter()
-        .map(|x| x.0)
-        .min()
-        .unwrap()
+    almanac(&input.1).apply(&seeds).min().unwrap()
 }
 
 #[test]
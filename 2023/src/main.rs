@@ -4,6 +4,7 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::io::stdout;
 
+mod cycle;
 mod day1;
 mod day10;
 mod day11;
@@ -19,6 +20,9 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod interval;
+mod memo;
+mod polygon;
 
 pub fn read_file(path: &str) -> Result<String> {
     let mut file = File::open(path)?;
@@ -0,0 +1,225 @@
+#![allow(dead_code)]
+
+use num::Integer;
+
+type Point = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Simple closed polygon on the integer lattice, the last vertex connects back to the first.
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Self {
+        assert!(vertices.len() >= 3);
+        Polygon { vertices }
+    }
+
+    /// Walk `(direction, length)` steps from `start`, the walk must end where it started.
+    pub fn from_steps(start: Point, steps: impl IntoIterator<Item = (Point, i64)>) -> Self {
+        let mut pos = start;
+        let mut vertices = vec![start];
+        for ((dx, dy), n) in steps {
+            pos = (pos.0 + dx * n, pos.1 + dy * n);
+            vertices.push(pos);
+        }
+        assert_eq!(vertices.pop(), Some(start), "steps don't close the polygon");
+        Polygon::new(vertices)
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.vertices
+            .iter()
+            .copied()
+            .zip(self.vertices.iter().copied().cycle().skip(1))
+    }
+
+    /// Twice the enclosed area, via the shoelace formula. Always an integer for lattice polygons.
+    pub fn double_area(&self) -> i64 {
+        let sum: i128 = self
+            .edges()
+            .map(|(a, b)| a.0 as i128 * b.1 as i128 - b.0 as i128 * a.1 as i128)
+            .sum();
+        sum.abs().try_into().unwrap()
+    }
+
+    /// Enclosed area, rounded down when it is a half integer.
+    pub fn area(&self) -> i64 {
+        self.double_area() / 2
+    }
+
+    /// Number of lattice points on the edges.
+    pub fn boundary_points(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| (b.0 - a.0).abs().gcd(&(b.1 - a.1).abs()))
+            .sum()
+    }
+
+    /// Number of lattice points strictly inside, via Pick's theorem.
+    pub fn interior_points(&self) -> i64 {
+        (self.double_area() - self.boundary_points() + 2) / 2
+    }
+
+    /// Interior and boundary points together, i.e. the tiles covered when every vertex is a tile.
+    pub fn lattice_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn locate(&self, p: Point) -> Location {
+        self.locate2((2 * p.0 as i128, 2 * p.1 as i128))
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.locate(p) != Location::Outside
+    }
+
+    /// Locate a point given in doubled coordinates, so half integer points can be tested too.
+    fn locate2(&self, p: (i128, i128)) -> Location {
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let a = (2 * a.0 as i128, 2 * a.1 as i128);
+            let b = (2 * b.0 as i128, 2 * b.1 as i128);
+
+            let cross = (b.0 - a.0) * (p.1 - a.1) - (p.0 - a.0) * (b.1 - a.1);
+            if cross == 0
+                && a.0.min(b.0) <= p.0
+                && p.0 <= a.0.max(b.0)
+                && a.1.min(b.1) <= p.1
+                && p.1 <= a.1.max(b.1)
+            {
+                return Location::Boundary;
+            }
+
+            // Count edges crossing the ray towards +x
+            if (a.1 > p.1) != (b.1 > p.1) && (cross > 0) == (b.1 > a.1) {
+                inside = !inside;
+            }
+        }
+
+        if inside {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+
+    /// Does any edge touch the open rectangle between `min` and `max`.
+    fn crosses_open(&self, min: Point, max: Point) -> bool {
+        self.edges().any(|(a, b)| {
+            if a.0.max(b.0) <= min.0 || a.0.min(b.0) >= max.0 {
+                return false;
+            }
+            if a.1.max(b.1) <= min.1 || a.1.min(b.1) >= max.1 {
+                return false;
+            }
+
+            // Separating axis along the edge normal
+            let (dx, dy) = ((b.0 - a.0) as i128, (b.1 - a.1) as i128);
+            let side = |c: Point| (dx * (c.1 - a.1) as i128 - dy * (c.0 - a.0) as i128).signum();
+            let corners = [min, (max.0, min.1), max, (min.0, max.1)].map(side);
+            !(corners.iter().all(|&s| s >= 0) || corners.iter().all(|&s| s <= 0))
+        })
+    }
+
+    /// Is the closed, axis-aligned rectangle spanned by `a` and `b` fully inside the polygon,
+    /// boundary included. Degenerate rectangles are only supported for rectilinear polygons.
+    pub fn contains_rect(&self, a: Point, b: Point) -> bool {
+        let min = (a.0.min(b.0), a.1.min(b.1));
+        let max = (a.0.max(b.0), a.1.max(b.1));
+
+        if min.0 < max.0 && min.1 < max.1 {
+            // Without edges inside the rectangle its interior lies wholly on one side
+            let center = (min.0 as i128 + max.0 as i128, min.1 as i128 + max.1 as i128);
+            return !self.crosses_open(min, max) && self.locate2(center) == Location::Inside;
+        }
+
+        assert!(
+            self.edges().all(|(a, b)| a.0 == b.0 || a.1 == b.1),
+            "degenerate rectangles need a rectilinear polygon"
+        );
+
+        // A line segment or a point, check it at every vertex and edge it meets and in between
+        let axis = if min.0 == max.0 { 1 } else { 0 };
+        let coord = |p: Point| if axis == 0 { p.0 } else { p.1 };
+        let other = |p: Point| if axis == 0 { p.1 } else { p.0 };
+        let lo = coord(min);
+        let hi = coord(max);
+        let line = other(min);
+
+        let mut events = vec![lo, hi];
+        for (a, b) in self.edges() {
+            if other(a).min(other(b)) <= line && line <= other(a).max(other(b)) {
+                events.extend(
+                    [coord(a), coord(b)]
+                        .into_iter()
+                        .filter(|c| lo < *c && *c < hi),
+                );
+            }
+        }
+        events.sort();
+        events.dedup();
+
+        let point2 = |c2: i128| {
+            if axis == 0 {
+                (c2, 2 * line as i128)
+            } else {
+                (2 * line as i128, c2)
+            }
+        };
+
+        events
+            .iter()
+            .all(|&c| self.locate2(point2(2 * c as i128)) != Location::Outside)
+            && events
+                .windows(2)
+                .all(|w| self.locate2(point2(w[0] as i128 + w[1] as i128)) != Location::Outside)
+    }
+}
+
+#[test]
+fn test() {
+    // 2023 day 18 example dig plan
+    let plan = "R 6,D 5,L 2,D 2,R 2,D 2,L 5,U 2,L 1,U 2,R 2,U 3,L 2,U 2";
+    let steps = plan.split(',').map(|s| {
+        let (d, n) = s.split_once(' ').unwrap();
+        let d = match d {
+            "R" => (1, 0),
+            "D" => (0, 1),
+            "L" => (-1, 0),
+            _ => (0, -1),
+        };
+        (d, n.parse().unwrap())
+    });
+    let polygon = Polygon::from_steps((0, 0), steps);
+    assert_eq!(polygon.area(), 42);
+    assert_eq!(polygon.boundary_points(), 38);
+    assert_eq!(polygon.interior_points(), 24);
+    assert_eq!(polygon.lattice_points(), 62);
+
+    assert_eq!(polygon.locate((1, 1)), Location::Inside);
+    assert_eq!(polygon.locate((6, 3)), Location::Boundary);
+    assert_eq!(polygon.locate((1, 3)), Location::Outside);
+
+    assert!(polygon.contains_rect((2, 0), (6, 5)));
+    assert!(!polygon.contains_rect((2, 0), (6, 6)));
+    assert!(polygon.contains_rect((0, 5), (6, 5)));
+    assert!(polygon.contains_rect((2, 3), (6, 3)));
+    assert!(!polygon.contains_rect((0, 3), (6, 3)));
+
+    let big = Polygon::new(vec![(0, 0), (3_000_000_000, 0), (0, 3_000_000_000)]);
+    assert_eq!(big.area(), 4_500_000_000_000_000_000);
+    assert!(big.contains_rect((1, 1), (1_499_999_999, 1_500_000_000)));
+    assert!(!big.contains_rect((1, 1), (1_500_000_000, 1_500_000_001)));
+}
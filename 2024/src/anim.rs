@@ -0,0 +1,159 @@
+use colored::{Color, Colorize};
+use eyre::Result;
+use std::fs::File;
+use std::io::{prelude::*, stdin, stdout, BufReader};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use crate::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub c: char,
+    pub color: Option<Color>,
+}
+
+impl From<char> for Cell {
+    fn from(c: char) -> Self {
+        Cell { c, color: None }
+    }
+}
+
+pub type Frame = Grid<Cell>;
+
+impl Grid<char> {
+    pub fn frame(&self, color: impl Fn(char) -> Option<Color>) -> Frame {
+        self.map(|_, _, c| Cell { c, color: color(c) })
+    }
+}
+
+/// Frame as text with ANSI colour codes, one line per row.
+pub fn render(frame: &Frame) -> String {
+    let mut s = String::new();
+    for (x, _, cell) in frame.iter() {
+        let c = cell.c.to_string();
+        match cell.color {
+            Some(color) => s.push_str(&c.color(color).to_string()),
+            None => s.push_str(&c),
+        }
+        if x + 1 == frame.size().0 {
+            s.push('\n');
+        }
+    }
+    s
+}
+
+// Frames in a recording are separated by a form feed line
+const SEPARATOR: &str = "\x0c\n";
+
+pub fn record(frames: impl IntoIterator<Item = Frame>, path: &str) -> Result<()> {
+    let mut file = File::create(path)?;
+    for frame in frames {
+        file.write_all(render(&frame).as_bytes())?;
+        file.write_all(SEPARATOR.as_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn load(path: &str) -> Result<Vec<String>> {
+    let contents = crate::read_file(path)?;
+    Ok(contents
+        .split(SEPARATOR)
+        .filter(|f| !f.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+enum Command {
+    Pause,
+    Step,
+    Faster,
+    Slower,
+    Quit,
+}
+
+// Stdin is line buffered, so commands are read on a thread and only take effect after enter
+fn commands() -> Receiver<Command> {
+    let (tx, rx) = channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdin()).lines() {
+            let command = match line.as_deref().map(str::trim) {
+                Ok("") | Ok("p") => Command::Pause,
+                Ok("s") | Ok("n") => Command::Step,
+                Ok("+") => Command::Faster,
+                Ok("-") => Command::Slower,
+                Ok("q") | Err(_) => Command::Quit,
+                Ok(_) => continue,
+            };
+            if tx.send(command).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Show the frames one after another in the terminal.
+/// Controls, each followed by enter: `p` or nothing pauses and resumes, `s` steps a single
+/// frame, `+` and `-` change speed and `q` quits.
+pub fn play(frames: impl IntoIterator<Item = String>, fps: u32) -> Result<()> {
+    let commands = commands();
+    let mut delay = Duration::from_secs(1) / fps.max(1);
+    let mut paused = false;
+    let mut out = stdout();
+
+    for (i, frame) in frames.into_iter().enumerate() {
+        write!(out, "\x1b[H\x1b[2J{}", frame)?;
+        writeln!(
+            out,
+            "frame {} @ {:.1} fps {}",
+            i,
+            1.0 / delay.as_secs_f64(),
+            if paused { "(paused)" } else { "" }
+        )?;
+        out.flush()?;
+
+        let mut step = false;
+        while !step {
+            let command = if paused {
+                commands.recv().ok()
+            } else {
+                std::thread::sleep(delay);
+                match commands.try_recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => break,
+                }
+            };
+
+            match command {
+                Some(Command::Pause) => paused = !paused,
+                Some(Command::Step) => {
+                    paused = true;
+                    step = true;
+                }
+                Some(Command::Faster) => delay = (delay / 2).max(Duration::from_millis(1)),
+                Some(Command::Slower) => delay *= 2,
+                Some(Command::Quit) | None => return Ok(()),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test() -> Result<()> {
+    colored::control::set_override(false);
+
+    let grid = Grid::from_str("#.\n.@");
+    let frame = grid.frame(|c| (c == '@').then_some(Color::Red));
+    assert_eq!(frame.get(1, 1).unwrap().color, Some(Color::Red));
+    assert_eq!(render(&frame), "#.\n.@\n");
+
+    let path = std::env::temp_dir().join("aoc-anim-test.txt");
+    let path = path.to_str().unwrap();
+    record([frame.clone(), frame.map(|_, _, _| Cell::from('x'))], path)?;
+    assert_eq!(load(path)?, ["#.\n.@\n", "xx\nxx\n"]);
+
+    Ok(())
+}
@@ -0,0 +1,316 @@
+#![allow(dead_code)]
+
+use eyre::{eyre, Result};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl Op {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "AND" => Ok(Op::And),
+            "OR" => Ok(Op::Or),
+            "XOR" => Ok(Op::Xor),
+            _ => Err(eyre!("Unknown gate {}", s)),
+        }
+    }
+
+    fn apply(&self, a: u64, b: u64) -> u64 {
+        match self {
+            Op::And => a & b,
+            Op::Or => a | b,
+            Op::Xor => a ^ b,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Op::And => "and",
+            Op::Or => "or",
+            Op::Xor => "xor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gate {
+    pub op: Op,
+    pub a: usize,
+    pub b: usize,
+    pub out: usize,
+}
+
+/// Gates over numbered wires, sorted so every gate comes after the gates driving its inputs.
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    pub gates: Vec<Gate>,
+}
+
+impl Circuit {
+    /// Build from (a, op, b, out) gates, failing if the gates form a loop.
+    pub fn compile<'a>(
+        gates: impl IntoIterator<Item = (&'a str, &'a str, &'a str, &'a str)>,
+    ) -> Result<Self> {
+        let mut circuit = Circuit {
+            names: Vec::new(),
+            index: HashMap::new(),
+            gates: Vec::new(),
+        };
+
+        let mut unsorted = Vec::new();
+        for (a, op, b, out) in gates {
+            let gate = Gate {
+                op: Op::parse(op)?,
+                a: circuit.intern(a),
+                b: circuit.intern(b),
+                out: circuit.intern(out),
+            };
+            unsorted.push(gate);
+        }
+
+        // Kahn's algorithm over the wires, a wire is ready once its gate has been placed
+        let mut driver = vec![None; circuit.names.len()];
+        for (i, gate) in unsorted.iter().enumerate() {
+            if driver[gate.out].replace(i).is_some() {
+                return Err(eyre!("Wire {} has two drivers", circuit.names[gate.out]));
+            }
+        }
+        let mut users = vec![Vec::new(); circuit.names.len()];
+        let mut waiting = vec![0; unsorted.len()];
+        for (i, gate) in unsorted.iter().enumerate() {
+            for wire in [gate.a, gate.b] {
+                if driver[wire].is_some() {
+                    users[wire].push(i);
+                    waiting[i] += 1;
+                }
+            }
+        }
+
+        let mut ready = (0..unsorted.len())
+            .filter(|&i| waiting[i] == 0)
+            .collect::<Vec<_>>();
+        while let Some(i) = ready.pop() {
+            let gate = unsorted[i];
+            circuit.gates.push(gate);
+            for &user in &users[gate.out] {
+                waiting[user] -= 1;
+                if waiting[user] == 0 {
+                    ready.push(user);
+                }
+            }
+        }
+
+        if circuit.gates.len() < unsorted.len() {
+            let mut stuck = (0..unsorted.len())
+                .filter(|&i| waiting[i] > 0)
+                .map(|i| circuit.names[unsorted[i].out].as_str())
+                .collect::<Vec<_>>();
+            stuck.sort();
+            return Err(eyre!("Combinational loop through {}", stuck.join(",")));
+        }
+
+        Ok(circuit)
+    }
+
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(&i) = self.index.get(name) {
+            return i;
+        }
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), self.names.len() - 1);
+        self.names.len() - 1
+    }
+
+    pub fn wire(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn name(&self, wire: usize) -> &str {
+        &self.names[wire]
+    }
+
+    pub fn wires(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Wires not driven by any gate, sorted by name.
+    pub fn inputs(&self) -> Vec<usize> {
+        let mut driven = vec![false; self.wires()];
+        for gate in &self.gates {
+            driven[gate.out] = true;
+        }
+        self.sorted((0..self.wires()).filter(|&w| !driven[w]))
+    }
+
+    /// Wires no gate reads, sorted by name.
+    pub fn outputs(&self) -> Vec<usize> {
+        let mut read = vec![false; self.wires()];
+        for gate in &self.gates {
+            read[gate.a] = true;
+            read[gate.b] = true;
+        }
+        self.sorted(self.gates.iter().map(|g| g.out).filter(|&w| !read[w]))
+    }
+
+    fn sorted(&self, wires: impl Iterator<Item = usize>) -> Vec<usize> {
+        let mut wires = wires.collect::<Vec<_>>();
+        wires.sort_by_key(|&w| &self.names[w]);
+        wires
+    }
+
+    /// Evaluate 64 input vectors at once, bit `k` of every value belongs to vector `k`.
+    /// Inputs have to be set, every gate output gets overwritten.
+    pub fn eval(&self, values: &mut [u64]) {
+        for gate in &self.gates {
+            values[gate.out] = gate.op.apply(values[gate.a], values[gate.b]);
+        }
+    }
+
+    // Wires of a bus like x00, x01, ... in bit order
+    fn bus(&self, prefix: char) -> Vec<usize> {
+        (0..)
+            .map_while(|i| self.wire(&format!("{}{:02}", prefix, i)))
+            .collect()
+    }
+
+    /// Put numbers on the input buses, one number per vector, and read back the output bus.
+    pub fn simulate(&self, inputs: &[(char, &[i64])], output: char) -> Vec<i64> {
+        let count = inputs.iter().map(|(_, v)| v.len()).max().unwrap_or(0);
+        let out = self.bus(output);
+
+        let mut results = Vec::new();
+        for start in (0..count).step_by(64) {
+            let lanes = (count - start).min(64);
+            let mut values = vec![0; self.wires()];
+            for (prefix, numbers) in inputs {
+                for (bit, wire) in self.bus(*prefix).into_iter().enumerate() {
+                    values[wire] = (0..lanes)
+                        .filter(|&k| numbers.get(start + k).is_some_and(|n| n >> bit & 1 == 1))
+                        .fold(0, |acc, k| acc | 1 << k);
+                }
+            }
+
+            self.eval(&mut values);
+            results.extend((0..lanes).map(|k| {
+                out.iter()
+                    .enumerate()
+                    .map(|(bit, &wire)| ((values[wire] >> k & 1) as i64) << bit)
+                    .sum::<i64>()
+            }));
+        }
+        results
+    }
+
+    /// Graphviz graph with a node per gate, named after its output wire.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph circuit {\n  rankdir=LR;\n");
+        for w in self.inputs() {
+            writeln!(out, "  {} [shape=box];", self.names[w]).unwrap();
+        }
+        for gate in &self.gates {
+            let name = &self.names[gate.out];
+            writeln!(
+                out,
+                "  {} [label=\"{}\\n{}\"];",
+                name,
+                name,
+                gate.op.name().to_uppercase()
+            )
+            .unwrap();
+            for input in [gate.a, gate.b] {
+                writeln!(out, "  {} -> {};", self.names[input], name).unwrap();
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Structural Verilog module built from gate primitives.
+    pub fn to_verilog(&self, module: &str) -> String {
+        let inputs = self.inputs();
+        let outputs = self.outputs();
+        let ports = inputs
+            .iter()
+            .map(|&w| format!("input {}", self.names[w]))
+            .chain(outputs.iter().map(|&w| format!("output {}", self.names[w])))
+            .collect::<Vec<_>>();
+
+        let mut out = format!("module {}(\n  {}\n);\n", module, ports.join(",\n  "));
+        let internal = self.sorted(
+            self.gates
+                .iter()
+                .map(|g| g.out)
+                .filter(|w| !outputs.contains(w)),
+        );
+        for w in internal {
+            writeln!(out, "  wire {};", self.names[w]).unwrap();
+        }
+        for (i, gate) in self.gates.iter().enumerate() {
+            writeln!(
+                out,
+                "  {} g{}({}, {}, {});",
+                gate.op.name(),
+                i,
+                self.names[gate.out],
+                self.names[gate.a],
+                self.names[gate.b]
+            )
+            .unwrap();
+        }
+        out.push_str("endmodule\n");
+        out
+    }
+}
+
+#[test]
+fn test() -> Result<()> {
+    // Two bit ripple-carry adder
+    let adder = [
+        ("x00", "XOR", "y00", "z00"),
+        ("x00", "AND", "y00", "c00"),
+        ("x01", "XOR", "y01", "h01"),
+        ("h01", "XOR", "c00", "z01"),
+        ("x01", "AND", "y01", "a01"),
+        ("h01", "AND", "c00", "t01"),
+        ("a01", "OR", "t01", "z02"),
+    ];
+    // Listed in reverse so compiling has to sort them
+    let circuit = Circuit::compile(adder.iter().rev().copied())?;
+    for (i, gate) in circuit.gates.iter().enumerate() {
+        for input in [gate.a, gate.b] {
+            assert!(circuit.gates[i..].iter().all(|g| g.out != input));
+        }
+    }
+
+    let x = (0..100).map(|i| i % 4).collect::<Vec<_>>();
+    let y = (0..100).map(|i| i / 4 % 4).collect::<Vec<_>>();
+    let z = circuit.simulate(&[('x', &x), ('y', &y)], 'z');
+    assert_eq!(z, x.iter().zip(&y).map(|(a, b)| a + b).collect::<Vec<_>>());
+
+    let loop_ = Circuit::compile([("a", "AND", "x", "b"), ("b", "OR", "y", "a")]);
+    assert_eq!(
+        loop_.unwrap_err().to_string(),
+        "Combinational loop through a,b"
+    );
+
+    let dot = circuit.to_dot();
+    assert!(dot.contains("x00 [shape=box];"));
+    assert!(dot.contains("h01 -> z01;"));
+
+    let verilog = circuit.to_verilog("adder");
+    assert!(verilog.starts_with("module adder(\n  input x00,"));
+    assert!(verilog.contains("  output z02\n);"));
+    assert!(verilog.contains("  wire c00;"));
+    assert!(verilog.contains(" or g6(z02, a01, t01);"));
+    assert!(verilog.ends_with("endmodule\n"));
+
+    Ok(())
+}
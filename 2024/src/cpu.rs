@@ -0,0 +1,487 @@
+#![allow(dead_code)]
+
+use eyre::{eyre, Result};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{BufRead, Write};
+
+/// Operand of the instructions that take a combo operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combo {
+    Literal(i64),
+    A,
+    B,
+    C,
+}
+
+impl Combo {
+    fn decode(imm: i64) -> Result<Self> {
+        match imm {
+            0..=3 => Ok(Combo::Literal(imm)),
+            4 => Ok(Combo::A),
+            5 => Ok(Combo::B),
+            6 => Ok(Combo::C),
+            _ => Err(eyre!("Invalid combo operand {}", imm)),
+        }
+    }
+
+    fn encode(&self) -> i64 {
+        match self {
+            Combo::Literal(n) => *n,
+            Combo::A => 4,
+            Combo::B => 5,
+            Combo::C => 6,
+        }
+    }
+}
+
+impl fmt::Display for Combo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Combo::Literal(n) => write!(f, "{}", n),
+            Combo::A => write!(f, "A"),
+            Combo::B => write!(f, "B"),
+            Combo::C => write!(f, "C"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instr {
+    /// A = A >> combo
+    Adv(Combo),
+    /// B = B ^ literal
+    Bxl(i64),
+    /// B = combo % 8
+    Bst(Combo),
+    /// Jump to literal unless A is 0
+    Jnz(i64),
+    /// B = B ^ C
+    Bxc,
+    /// Output combo % 8
+    Out(Combo),
+    /// B = A >> combo
+    Bdv(Combo),
+    /// C = A >> combo
+    Cdv(Combo),
+}
+
+impl Instr {
+    pub fn decode(op: i64, imm: i64) -> Result<Self> {
+        if !(0..8).contains(&imm) {
+            return Err(eyre!("Invalid operand {}", imm));
+        }
+        Ok(match op {
+            0 => Instr::Adv(Combo::decode(imm)?),
+            1 => Instr::Bxl(imm),
+            2 => Instr::Bst(Combo::decode(imm)?),
+            3 => Instr::Jnz(imm),
+            4 => Instr::Bxc,
+            5 => Instr::Out(Combo::decode(imm)?),
+            6 => Instr::Bdv(Combo::decode(imm)?),
+            7 => Instr::Cdv(Combo::decode(imm)?),
+            _ => return Err(eyre!("Invalid opcode {}", op)),
+        })
+    }
+
+    pub fn encode(&self) -> [i64; 2] {
+        match self {
+            Instr::Adv(x) => [0, x.encode()],
+            Instr::Bxl(n) => [1, *n],
+            Instr::Bst(x) => [2, x.encode()],
+            Instr::Jnz(n) => [3, *n],
+            // The operand of bxc is ignored
+            Instr::Bxc => [4, 0],
+            Instr::Out(x) => [5, x.encode()],
+            Instr::Bdv(x) => [6, x.encode()],
+            Instr::Cdv(x) => [7, x.encode()],
+        }
+    }
+}
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instr::Adv(x) => write!(f, "adv {}", x),
+            Instr::Bxl(n) => write!(f, "bxl {}", n),
+            Instr::Bst(x) => write!(f, "bst {}", x),
+            Instr::Jnz(n) => write!(f, "jnz {}", n),
+            Instr::Bxc => write!(f, "bxc"),
+            Instr::Out(x) => write!(f, "out {}", x),
+            Instr::Bdv(x) => write!(f, "bdv {}", x),
+            Instr::Cdv(x) => write!(f, "cdv {}", x),
+        }
+    }
+}
+
+pub fn disassemble(prog: &[i64]) -> Result<Vec<Instr>> {
+    prog.chunks(2)
+        .map(|i| match i {
+            &[op, imm] => Instr::decode(op, imm),
+            _ => Err(eyre!("Opcode {} without operand", i[0])),
+        })
+        .collect()
+}
+
+/// Turn mnemonics, one instruction per line, back into the program. Text after `#` is ignored.
+pub fn assemble(text: &str) -> Result<Vec<i64>> {
+    let mut prog = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let error = || eyre!("Line {}: can't assemble '{}'", n + 1, line);
+        let mut words = line.split_whitespace();
+        let mnemonic = words.next().unwrap();
+        let operand = words.next();
+        if words.next().is_some() {
+            return Err(error());
+        }
+
+        let literal = || -> Result<i64> {
+            operand
+                .and_then(|s| s.parse().ok())
+                .filter(|n| (0..8).contains(n))
+                .ok_or_else(error)
+        };
+        let combo = || -> Result<Combo> {
+            match operand {
+                Some("A") => Ok(Combo::A),
+                Some("B") => Ok(Combo::B),
+                Some("C") => Ok(Combo::C),
+                _ => literal()
+                    .ok()
+                    .filter(|n| *n < 4)
+                    .map(Combo::Literal)
+                    .ok_or_else(error),
+            }
+        };
+
+        let instr = match mnemonic {
+            "adv" => Instr::Adv(combo()?),
+            "bxl" => Instr::Bxl(literal()?),
+            "bst" => Instr::Bst(combo()?),
+            "jnz" => Instr::Jnz(literal()?),
+            "bxc" if operand.is_none() => Instr::Bxc,
+            "out" => Instr::Out(combo()?),
+            "bdv" => Instr::Bdv(combo()?),
+            "cdv" => Instr::Cdv(combo()?),
+            _ => return Err(error()),
+        };
+        prog.extend(instr.encode());
+    }
+    Ok(prog)
+}
+
+/// The machine state before executing an instruction, and what it printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub pc: usize,
+    pub instr: Instr,
+    pub a: i64,
+    pub b: i64,
+    pub c: i64,
+    pub out: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cpu {
+    pub a: i64,
+    pub b: i64,
+    pub c: i64,
+    pub pc: usize,
+    pub prog: Vec<i64>,
+    pub output: Vec<i64>,
+}
+
+impl Cpu {
+    pub fn new(a: i64, b: i64, c: i64, prog: &[i64]) -> Self {
+        Cpu {
+            a,
+            b,
+            c,
+            pc: 0,
+            prog: prog.to_vec(),
+            output: Vec::new(),
+        }
+    }
+
+    /// The next instruction, None once the program counter runs off the end. Fails if the
+    /// program counter lands on something that doesn't decode.
+    pub fn fetch(&self) -> Result<Option<Instr>> {
+        let end = self.pc.checked_add(2);
+        match end.and_then(|end| self.prog.get(self.pc..end)) {
+            Some(&[op, imm]) => Instr::decode(op, imm)
+                .map(Some)
+                .map_err(|e| eyre!("At {}: {}", self.pc, e)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn halted(&self) -> bool {
+        matches!(self.fetch(), Ok(None))
+    }
+
+    fn combo(&self, x: Combo) -> i64 {
+        match x {
+            Combo::Literal(n) => n,
+            Combo::A => self.a,
+            Combo::B => self.b,
+            Combo::C => self.c,
+        }
+    }
+
+    // A divided by 2 to the power of the combo operand, 0 once the divisor outgrows any i64
+    fn divide(&self, x: Combo) -> Result<i64> {
+        let n = self.combo(x);
+        let n = u32::try_from(n).map_err(|_| eyre!("At {}: negative shift {}", self.pc, n))?;
+        Ok(match 1_i64.checked_shl(n) {
+            Some(d) if d > 0 => self.a / d,
+            _ => 0,
+        })
+    }
+
+    /// Execute a single instruction, None if the machine has halted.
+    pub fn step(&mut self) -> Result<Option<Step>> {
+        let Some(instr) = self.fetch()? else {
+            return Ok(None);
+        };
+        let mut step = Step {
+            pc: self.pc,
+            instr,
+            a: self.a,
+            b: self.b,
+            c: self.c,
+            out: None,
+        };
+
+        // Fail before anything changes
+        let quotient = match instr {
+            Instr::Adv(x) | Instr::Bdv(x) | Instr::Cdv(x) => self.divide(x)?,
+            _ => 0,
+        };
+
+        self.pc += 2;
+        match instr {
+            Instr::Adv(_) => self.a = quotient,
+            Instr::Bxl(n) => self.b ^= n,
+            Instr::Bst(x) => self.b = self.combo(x) % 8,
+            Instr::Jnz(n) => {
+                if self.a != 0 {
+                    self.pc = n as usize;
+                }
+            }
+            Instr::Bxc => self.b ^= self.c,
+            Instr::Out(x) => {
+                let out = self.combo(x) % 8;
+                self.output.push(out);
+                step.out = Some(out);
+            }
+            Instr::Bdv(_) => self.b = quotient,
+            Instr::Cdv(_) => self.c = quotient,
+        }
+
+        Ok(Some(step))
+    }
+
+    /// Run until the machine halts and return everything it printed.
+    pub fn run(&mut self) -> Result<&[i64]> {
+        while self.step()?.is_some() {}
+        Ok(&self.output)
+    }
+
+    /// Every step the machine takes until it halts, ending with the error if one fails.
+    pub fn trace(&mut self) -> impl Iterator<Item = Result<Step>> + '_ {
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            let step = self.step().transpose();
+            failed = matches!(step, Some(Err(_)));
+            step
+        })
+    }
+
+    fn registers(&self) -> String {
+        format!("pc={} A={} B={} C={}", self.pc, self.a, self.b, self.c)
+    }
+}
+
+/// Interactive debugger reading commands from `input`:
+/// `s [n]` steps, `c` continues to the next breakpoint, `b <pc>` toggles a breakpoint,
+/// `r` shows the registers, `set <reg> <value>` changes one, `l` lists the program,
+/// `o` shows the output so far and `q` quits.
+pub fn debug(cpu: &mut Cpu, input: impl BufRead, mut out: impl Write) -> Result<()> {
+    let mut breakpoints = BTreeSet::<usize>::new();
+
+    writeln!(out, "{}", cpu.registers())?;
+    for line in input.lines() {
+        let line = line?;
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words[..] {
+            ["s"] | ["s", _] => match words.get(1).map_or(Ok(1), |n| n.parse::<usize>()) {
+                Ok(n) => {
+                    for _ in 0..n {
+                        match cpu.step() {
+                            Ok(Some(step)) => writeln!(out, "{:>3}: {}", step.pc, step.instr)?,
+                            Ok(None) => break,
+                            Err(e) => {
+                                writeln!(out, "error: {}", e)?;
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => writeln!(out, "bad step count '{}': {}", words[1], e)?,
+            },
+            ["c"] => loop {
+                match cpu.step() {
+                    Ok(Some(step)) if breakpoints.contains(&cpu.pc) => {
+                        writeln!(out, "break at {} after {}", cpu.pc, step.instr)?;
+                        break;
+                    }
+                    Ok(Some(_)) => (),
+                    Ok(None) => break,
+                    Err(e) => {
+                        writeln!(out, "error: {}", e)?;
+                        break;
+                    }
+                }
+            },
+            ["b", pc] => match pc.parse() {
+                Ok(pc) => {
+                    if !breakpoints.remove(&pc) {
+                        breakpoints.insert(pc);
+                    }
+                    writeln!(out, "breakpoints {:?}", breakpoints)?;
+                }
+                Err(e) => writeln!(out, "bad breakpoint '{}': {}", pc, e)?,
+            },
+            ["set", "pc", value] => match value.parse() {
+                Ok(pc) => cpu.pc = pc,
+                Err(e) => writeln!(out, "bad pc '{}': {}", value, e)?,
+            },
+            ["set", reg, value] => match value.parse() {
+                Ok(value) => match reg {
+                    "A" | "a" => cpu.a = value,
+                    "B" | "b" => cpu.b = value,
+                    "C" | "c" => cpu.c = value,
+                    _ => writeln!(out, "unknown register {}", reg)?,
+                },
+                Err(e) => writeln!(out, "bad value '{}': {}", value, e)?,
+            },
+            ["r"] => (),
+            ["l"] => {
+                // Decoded one at a time, so a bad instruction only spoils its own line
+                for (i, chunk) in cpu.prog.chunks(2).enumerate() {
+                    let pc = i * 2;
+                    let mark = if pc == cpu.pc { ">" } else { " " };
+                    let brk = if breakpoints.contains(&pc) { "*" } else { " " };
+                    let instr = match disassemble(chunk) {
+                        Ok(instr) => instr[0].to_string(),
+                        Err(e) => format!("?? {}", e),
+                    };
+                    writeln!(out, "{}{}{:>3}: {}", mark, brk, pc, instr)?;
+                }
+            }
+            ["o"] => writeln!(out, "{:?}", cpu.output)?,
+            ["q"] => return Ok(()),
+            _ => writeln!(out, "unknown command '{}'", line)?,
+        }
+
+        if cpu.halted() {
+            writeln!(out, "halted, output {:?}", cpu.output)?;
+        }
+        writeln!(out, "{}", cpu.registers())?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test() -> Result<()> {
+    let prog = [0, 1, 5, 4, 3, 0];
+    let mut cpu = Cpu::new(729, 0, 0, &prog);
+    assert_eq!(cpu.run()?, [4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+
+    let listing = disassemble(&prog)?;
+    assert_eq!(
+        listing,
+        [
+            Instr::Adv(Combo::Literal(1)),
+            Instr::Out(Combo::A),
+            Instr::Jnz(0)
+        ]
+    );
+    let text = listing
+        .iter()
+        .map(|i| format!("{}\n", i))
+        .collect::<String>();
+    assert_eq!(text, "adv 1\nout A\njnz 0\n");
+    assert_eq!(assemble(&text)?, prog);
+    assert_eq!(assemble("  bxc # B ^= C\n\nbst 3")?, [4, 0, 2, 3]);
+    assert!(assemble("bst 7").is_err());
+    assert!(assemble("jmp 0").is_err());
+    assert!(disassemble(&[2, 7]).is_err());
+
+    let mut cpu = Cpu::new(10, 0, 0, &prog);
+    let trace = cpu.trace().collect::<Result<Vec<_>>>()?;
+    assert_eq!(trace.len(), 3 * 4);
+    assert_eq!(trace[1].out, Some(5));
+    assert_eq!(trace[3].a, 5);
+
+    let mut cpu = Cpu::new(10, 0, 0, &prog);
+    let mut log = Vec::new();
+    debug(
+        &mut cpu,
+        "b 4\nc\nr\nset A 0\ns 2\nq\n".as_bytes(),
+        &mut log,
+    )?;
+    let log = String::from_utf8(log)?;
+    assert!(log.contains("break at 4 after out A"));
+    assert!(log.contains("halted, output [5]"));
+
+    // Bad arguments are reported without ending the session, and so is jumping into the middle
+    // of an instruction that doesn't decode
+    let mut cpu = Cpu::new(10, 0, 0, &[1, 2, 7, 4]);
+    let mut log = Vec::new();
+    debug(
+        &mut cpu,
+        "s x\nb foo\nset A x\nset pc 1\ns\nset pc -1\nq\n".as_bytes(),
+        &mut log,
+    )?;
+    let log = String::from_utf8(log)?;
+    assert!(log.contains("bad step count 'x'"));
+    assert!(log.contains("bad breakpoint 'foo'"));
+    assert!(log.contains("bad value 'x'"));
+    assert!(log.contains("error: At 1: Invalid combo operand 7"));
+    assert!(log.contains("bad pc '-1'"));
+    assert!(cpu.run().is_err());
+    cpu.pc = usize::MAX;
+    assert!(cpu.halted());
+
+    // Programs that don't disassemble can still be debugged up to the bad instruction
+    let mut cpu = Cpu::new(10, 0, 0, &[1, 2, 2, 7, 3]);
+    let mut log = Vec::new();
+    debug(&mut cpu, "l\ns\ns\nq\n".as_bytes(), &mut log)?;
+    let log = String::from_utf8(log)?;
+    assert!(log.contains(">   0: bxl 2"));
+    assert!(log.contains("    2: ?? Invalid combo operand 7"));
+    assert!(log.contains("    4: ?? Opcode 3 without operand"));
+    assert!(log.contains("  0: bxl 2\npc=2"));
+    assert!(log.contains("error: At 2: Invalid combo operand 7"));
+
+    // cdv B with shifts past every bit of A, or shifts the wrong way
+    let mut cpu = Cpu::new(10, 64, 0, &[7, 5, 3, 0]);
+    assert!(cpu.step()?.is_some());
+    assert_eq!(cpu.c, 0);
+    let mut cpu = Cpu::new(10, -1, 0, &[7, 5, 3, 0]);
+    assert!(cpu.step().is_err());
+    assert_eq!(cpu.pc, 0);
+
+    Ok(())
+}
@@ -1,9 +1,11 @@
-use std::collections::HashSet;
-
+use colored::Color;
 use eyre::{Report, Result};
 use num::Integer;
 use regex::{Match, Regex};
 
+use crate::anim::{render, Frame};
+use crate::Grid;
+
 type Input = Vec<(Point, Point)>;
 type Output = i64;
 type Point = (i64, i64);
@@ -26,8 +28,8 @@ pub fn parse(input: &str) -> Result<Input> {
         .collect::<Result<_>>()
 }
 
-fn steps(input: &Input, size: Point, secs: i64) -> Output {
-    let bots = input
+fn bots(input: &Input, size: Point, secs: i64) -> Vec<Point> {
+    input
         .iter()
         .map(|&(p, v)| {
             let x = (p.0 + v.0 * secs).mod_floor(&size.0);
@@ -38,7 +40,11 @@ fn steps(input: &Input, size: Point, secs: i64) -> Output {
 
             (x, y)
         })
-        .collect::<Vec<Point>>();
+        .collect()
+}
+
+fn steps(input: &Input, size: Point, secs: i64) -> Output {
+    let bots = bots(input, size, secs);
 
     let xhalf = size.0 / 2;
     let yhalf = size.1 / 2;
@@ -63,18 +69,19 @@ fn steps(input: &Input, size: Point, secs: i64) -> Output {
     quads.iter().product()
 }
 
-fn print(bots: &[Point], size: Point) {
-    let set = HashSet::<Point>::from_iter(bots.iter().copied());
-    for y in 0..size.1 {
-        for x in 0..size.0 {
-            if set.contains(&(x, y)) {
-                print!("X");
-            } else {
-                print!(" ");
-            }
-        }
-        println!()
+fn picture(bots: &[Point], size: Point) -> Grid<char> {
+    let mut grid = Grid::with(size.0, size.1, ' ');
+    for &(x, y) in bots {
+        *grid.get_mut(x, y).unwrap() = 'X';
     }
+    grid
+}
+
+pub fn frames(input: Input) -> impl Iterator<Item = Frame> {
+    let size = (101, 103);
+    (0..size.0 * size.1).map(move |secs| {
+        picture(&bots(&input, size, secs), size).frame(|c| (c == 'X').then_some(Color::Green))
+    })
 }
 
 pub fn part1(input: &Input) -> Output {
@@ -85,18 +92,7 @@ pub fn part2(input: &Input) -> Output {
     let size = (101, 103);
 
     for secs in 0.. {
-        let bots = input
-            .iter()
-            .map(|&(p, v)| {
-                let x = (p.0 + v.0 * secs).mod_floor(&size.0);
-                let y = (p.1 + v.1 * secs).mod_floor(&size.1);
-
-                assert!(0 <= x && x < size.0);
-                assert!(0 <= y && y < size.1);
-
-                (x, y)
-            })
-            .collect::<Vec<Point>>();
+        let bots = bots(input, size, secs);
 
         let score = bots
             .iter()
@@ -110,7 +106,7 @@ pub fn part2(input: &Input) -> Output {
 
         if score > 500 {
             if cfg!(test) {
-                print(&bots, size);
+                print!("{}", render(&picture(&bots, size).frame(|_| None)));
             }
             return secs;
         }
@@ -1,6 +1,8 @@
+use colored::Color;
 use eyre::{OptionExt, Result};
 use std::{collections::HashSet, fmt::Write};
 
+use crate::anim::{Cell, Frame};
 use crate::Grid;
 
 type Input = (Grid<char>, Vec<char>);
@@ -68,10 +70,8 @@ pub fn part1(input: &Input) -> Output {
         .sum()
 }
 
-pub fn part2(input: &Input) -> Output {
-    let (grid, moves) = input;
-
-    let mut grid = Grid::from_str(
+fn widen(grid: &Grid<char>) -> Grid<char> {
+    Grid::from_str(
         grid.iter()
             .fold(String::new(), |mut acc, (x, _, c)| {
                 let s = match c {
@@ -91,72 +91,87 @@ pub fn part2(input: &Input) -> Output {
                 acc
             })
             .as_str(),
-    );
+    )
+}
 
-    let mut pos = grid
+fn robot(grid: &mut Grid<char>) -> (i64, i64) {
+    let pos = grid
         .iter()
         .find_map(|(x, y, c)| (c == '@').then_some((x, y)))
         .unwrap();
     (*grid.get_mut(pos.0, pos.1).unwrap()) = '.';
+    pos
+}
 
-    for m in moves {
-        let dir = match *m {
-            '>' => (1, 0),
-            '<' => (-1, 0),
-            'v' => (0, 1),
-            '^' => (0, -1),
-            _ => unreachable!(),
-        };
+fn push_wide(grid: &mut Grid<char>, pos: (i64, i64), m: char) -> (i64, i64) {
+    let dir = match m {
+        '>' => (1, 0),
+        '<' => (-1, 0),
+        'v' => (0, 1),
+        '^' => (0, -1),
+        _ => unreachable!(),
+    };
+
+    let next = (pos.0 + dir.0, pos.1 + dir.1);
+    let dest = grid.get(next.0, next.1).unwrap();
+    match dest {
+        '.' => next,
+        '#' => pos,
+        '[' | ']' => {
+            fn moving(
+                grid: &Grid<char>,
+                pieces: &mut HashSet<(i64, i64, char)>,
+                dir: (i64, i64),
+                pos: (i64, i64),
+            ) -> bool {
+                let c = grid.get(pos.0, pos.1).unwrap();
+                let (other, delta) = match c {
+                    '.' => return true,
+                    '#' => return false,
+                    '[' => (']', 1),
+                    ']' => ('[', -1),
+                    _ => unreachable!(),
+                };
 
-        let next = (pos.0 + dir.0, pos.1 + dir.1);
-        let dest = grid.get(next.0, next.1).unwrap();
-        match dest {
-            '.' => pos = next,
-            '#' => (),
-            '[' | ']' => {
-                fn moving(
-                    grid: &Grid<char>,
-                    pieces: &mut HashSet<(i64, i64, char)>,
-                    dir: (i64, i64),
-                    pos: (i64, i64),
-                ) -> bool {
-                    let c = grid.get(pos.0, pos.1).unwrap();
-                    let (other, delta) = match c {
-                        '.' => return true,
-                        '#' => return false,
-                        '[' => (']', 1),
-                        ']' => ('[', -1),
-                        _ => unreachable!(),
-                    };
-
-                    if dir.0 != 0 {
-                        pieces.insert((pos.0, pos.1, c));
-                        pieces.insert((pos.0 + dir.0, pos.1, other));
-                        let n = (pos.0 + 2 * dir.0, pos.1);
-                        moving(grid, pieces, dir, n)
-                    } else {
-                        pieces.insert((pos.0, pos.1, c));
-                        pieces.insert((pos.0 + delta, pos.1, other));
-                        moving(grid, pieces, dir, (pos.0, pos.1 + dir.1))
-                            && moving(grid, pieces, dir, (pos.0 + delta, pos.1 + dir.1))
-                    }
+                if dir.0 != 0 {
+                    pieces.insert((pos.0, pos.1, c));
+                    pieces.insert((pos.0 + dir.0, pos.1, other));
+                    let n = (pos.0 + 2 * dir.0, pos.1);
+                    moving(grid, pieces, dir, n)
+                } else {
+                    pieces.insert((pos.0, pos.1, c));
+                    pieces.insert((pos.0 + delta, pos.1, other));
+                    moving(grid, pieces, dir, (pos.0, pos.1 + dir.1))
+                        && moving(grid, pieces, dir, (pos.0 + delta, pos.1 + dir.1))
                 }
+            }
 
-                let mut pieces = HashSet::new();
-                if moving(&grid, &mut pieces, dir, next) {
-                    for (x, y, _) in &pieces {
-                        *(grid.get_mut(*x, *y).unwrap()) = '.';
-                    }
-
-                    for (x, y, c) in &pieces {
-                        *(grid.get_mut(*x + dir.0, *y + dir.1).unwrap()) = *c;
-                    }
+            let mut pieces = HashSet::new();
+            if moving(grid, &mut pieces, dir, next) {
+                for (x, y, _) in &pieces {
+                    *(grid.get_mut(*x, *y).unwrap()) = '.';
+                }
 
-                    pos = next;
+                for (x, y, c) in &pieces {
+                    *(grid.get_mut(*x + dir.0, *y + dir.1).unwrap()) = *c;
                 }
+
+                next
+            } else {
+                pos
             }
-            _ => unreachable!(),
         }
+        _ => unreachable!(),
+    }
+}
+
+pub fn part2(input: &Input) -> Output {
+    let (grid, moves) = input;
+
+    let mut grid = widen(grid);
+    let mut pos = robot(&mut grid);
+    for m in moves {
+        pos = push_wide(&mut grid, pos, *m);
     }
 
     grid.iter()
@@ -164,6 +179,31 @@ pub fn part2(input: &Input) -> Output {
         .sum()
 }
 
+pub fn frames(input: Input) -> impl Iterator<Item = Frame> {
+    let (grid, moves) = input;
+
+    let mut grid = widen(&grid);
+    let mut pos = robot(&mut grid);
+    std::iter::once(None)
+        .chain(moves.into_iter().map(Some))
+        .map(move |m| {
+            if let Some(m) = m {
+                pos = push_wide(&mut grid, pos, m);
+            }
+
+            let mut frame = grid.frame(|c| match c {
+                '#' => Some(Color::BrightBlack),
+                '[' | ']' => Some(Color::Yellow),
+                _ => None,
+            });
+            *frame.get_mut(pos.0, pos.1).unwrap() = Cell {
+                c: '@',
+                color: Some(Color::Red),
+            };
+            frame
+        })
+}
+
 #[test]
 fn test() -> Result<()> {
     use crate::read_file;
@@ -1,12 +1,12 @@
-use std::{cmp::Reverse, collections::BinaryHeap};
-
+use crate::search::{self, Search};
 use crate::Grid;
 use eyre::Result;
+use itertools::Itertools;
 
 type Input = Grid<char>;
 type Output = i64;
 
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 struct Point(i64, i64);
 
 impl Point {
@@ -14,10 +14,6 @@ impl Point {
         Point(self.0 + other.0, self.1 + other.1)
     }
 
-    fn sub(&self, other: Self) -> Self {
-        Point(self.0 - other.0, self.1 - other.1)
-    }
-
     fn clock(&self) -> Self {
         Point(-self.1, self.0)
     }
@@ -25,26 +21,6 @@ impl Point {
     fn counter(&self) -> Self {
         Point(self.1, -self.0)
     }
-
-    fn index(&self) -> usize {
-        match (self.0, self.1) {
-            (0, 1) => 0,
-            (1, 0) => 1,
-            (0, -1) => 2,
-            (-1, 0) => 3,
-            _ => unreachable!(),
-        }
-    }
-
-    fn dir(i: usize) -> Self {
-        match i {
-            0 => Self(0, 1),
-            1 => Self(1, 0),
-            2 => Self(0, -1),
-            3 => Self(-1, 0),
-            _ => unreachable!(),
-        }
-    }
 }
 
 impl From<(i64, i64)> for Point {
@@ -53,112 +29,54 @@ impl From<(i64, i64)> for Point {
     }
 }
 
-#[derive(PartialEq, PartialOrd, Eq, Ord)]
-struct Cursor(i64, Point, Point);
-
 pub fn parse(input: &str) -> Result<Input> {
     Ok(Grid::from_str(input))
 }
 
-pub fn part1(input: &Input) -> Output {
-    let s = input
+fn find(input: &Input, c: char) -> Point {
+    input
         .iter()
-        .find_map(|(x, y, c)| (c == 'S').then_some((x, y).into()))
-        .unwrap();
-    let dir = (1, 0).into();
-
-    let mut visited = input.map(|_, _, _| [None; 4]);
-    let mut queue = BinaryHeap::new();
-
-    queue.push(Reverse(Cursor(0, s, dir)));
-    while let Some(Reverse(Cursor(score, pos, dir))) = queue.pop() {
-        let cell = input.get(pos.0, pos.1);
-        if cell.is_none() || cell == Some('#') {
-            continue;
-        }
-
-        if cell == Some('E') {
-            return score;
-        }
-
-        let v = &mut visited.get_mut(pos.0, pos.1).unwrap()[dir.index()];
-        if v.is_none() {
-            *v = Some(score);
-            queue.push(Reverse(Cursor(score + 1, pos.add(dir), dir)));
-            queue.push(Reverse(Cursor(score + 1000, pos, dir.clock())));
-            queue.push(Reverse(Cursor(score + 1000, pos, dir.counter())));
-        } else {
-            assert!(v.unwrap() <= score);
-        }
-    }
-
-    unreachable!()
+        .find_map(|(x, y, v)| (v == c).then_some((x, y).into()))
+        .unwrap()
 }
 
-pub fn part2(input: &Input) -> Output {
-    let s = input
-        .iter()
-        .find_map(|(x, y, c)| (c == 'S').then_some((x, y).into()))
-        .unwrap();
-    let e: Point = input
-        .iter()
-        .find_map(|(x, y, c)| (c == 'E').then_some((x, y).into()))
-        .unwrap();
-    let dir = (1, 0).into();
-
-    let mut visited = input.map(|_, _, _| [None; 4]);
-    let mut queue = BinaryHeap::new();
-
-    queue.push(Reverse(Cursor(0, s, dir)));
-    while let Some(Reverse(Cursor(score, pos, dir))) = queue.pop() {
-        let cell = input.get(pos.0, pos.1);
-        if cell.is_none() || cell == Some('#') {
-            continue;
-        }
-
-        let v = &mut visited.get_mut(pos.0, pos.1).unwrap()[dir.index()];
-        if v.is_none() {
-            *v = Some(score);
-
-            if cell != Some('E') {
-                queue.push(Reverse(Cursor(score + 1, pos.add(dir), dir)));
-                queue.push(Reverse(Cursor(score + 1000, pos, dir.clock())));
-                queue.push(Reverse(Cursor(score + 1000, pos, dir.counter())));
-            }
-        } else {
-            assert!(v.unwrap() <= score);
-        }
+// With `all` every best path is kept rather than just one
+fn search(
+    input: &Input,
+    all: bool,
+    goal: impl FnMut(&(Point, Point)) -> bool,
+) -> Search<(Point, Point)> {
+    let neighbours = |&(pos, dir): &(Point, Point)| {
+        let next = pos.add(dir);
+        let forward = (input.get(next.0, next.1) != Some('#')).then_some(((next, dir), 1));
+        [((pos, dir.clock()), 1000), ((pos, dir.counter()), 1000)]
+            .into_iter()
+            .chain(forward)
+    };
+    let start = (find(input, 'S'), Point(1, 0));
+    if all {
+        search::dijkstra_all(start, neighbours, goal)
+    } else {
+        search::dijkstra(start, neighbours, goal)
     }
+}
 
-    let score = visited
-        .get(e.0, e.1)
+pub fn part1(input: &Input) -> Output {
+    let e = find(input, 'E');
+    search(input, false, |&(pos, _)| pos == e)
+        .distance()
         .unwrap()
-        .iter()
-        .filter_map(|c| *c)
-        .min()
-        .unwrap();
-
-    let mut path = input.map(|_, _, _| false);
-    let mut work = Vec::new();
-
-    for (i, x) in visited.get(e.0, e.1).unwrap().iter().enumerate() {
-        if *x == Some(score) {
-            work.push((score, e, Point::dir(i)))
-        }
-    }
-
-    while let Some((score, pos, dir)) = work.pop() {
-        if visited.get(pos.0, pos.1).and_then(|dirs| dirs[dir.index()]) == Some(score) {
-            *(path.get_mut(pos.0, pos.1).unwrap()) = true;
-
-            work.push((score - 1, pos.sub(dir), dir));
-            work.push((score - 1000, pos, dir.counter()));
-            work.push((score - 1000, pos, dir.clock()));
-        }
-    }
+}
 
-    path.iter()
-        .filter(|(_, _, x)| *x)
+pub fn part2(input: &Input) -> Output {
+    let e = find(input, 'E');
+    let search = search(input, true, |&(pos, _)| pos == e);
+
+    search
+        .on_any_path()
+        .into_iter()
+        .map(|(pos, _)| pos)
+        .unique()
         .count()
         .try_into()
         .unwrap()
@@ -1,5 +1,6 @@
-use eyre::Result;
-use std::ops::Div;
+use eyre::{eyre, Result};
+
+use crate::cpu::Cpu;
 
 type Input = (i64, i64, i64, Vec<i64>);
 type Output = String;
@@ -29,117 +30,105 @@ pub fn parse(input: &str) -> Result<Input> {
     Ok((a, b, c, prog))
 }
 
-pub fn part1(input: &Input) -> Output {
-    let (mut a, mut b, mut c, prog) = input;
-    let mut pc = 0;
-    let mut output = Vec::new();
-
-    fn combo(imm: i64, a: i64, b: i64, c: i64) -> i64 {
-        match imm {
-            0..=3 => imm,
-            4 => a,
-            5 => b,
-            6 => c,
-            _ => unreachable!(),
-        }
-    }
+fn run(a: i64, b: i64, c: i64, prog: &[i64]) -> Result<Vec<i64>> {
+    Ok(Cpu::new(a, b, c, prog).run()?.to_vec())
+}
 
-    while let Some(&[op, imm]) = prog.get(pc..pc + 2) {
-        let mut next_pc = None;
-        match op {
-            0 => a = a.div(&(1 << combo(imm, a, b, c))),
-            1 => b ^= imm,
-            2 => b = combo(imm, a, b, c) % 8,
-            3 => {
-                if a != 0 {
-                    next_pc = Some(imm.try_into().unwrap())
-                }
-            }
-            4 => b ^= c,
-            5 => output.push(combo(imm, a, b, c) % 8),
-            6 => b = a.div(&(1 << combo(imm, a, b, c))),
-            7 => c = a.div(&(1 << combo(imm, a, b, c))),
-            _ => unreachable!(),
-        }
+pub fn part1(input: &Input) -> Result<Output> {
+    let (a, b, c, prog) = input;
 
-        pc = next_pc.unwrap_or(pc + 2);
-    }
-
-    output
+    Ok(run(*a, *b, *c, prog)?
         .iter()
         .map(|x| format!("{}", x))
         .reduce(|a, b| format!("{},{}", a, b))
-        .unwrap()
+        .unwrap_or_default())
 }
 
-pub fn part2(input: &Input) -> i64 {
-    // b = a % 8
-    // b ^= 2
-    // c = a >> b
-    // b ^= c
-    // b ^= 3
-    // out b
-    // a = a >> 3
-
-    // Hard-coded :(
-    fn run_backwards(r: &mut Vec<i64>, a: i64, output: &[i64]) {
-        if output.is_empty() {
-            r.push(a);
-            return;
-        }
-
-        for b in 0..8 {
-            let prev_a = (a << 3) | b;
+// The search below only works for a single loop that shifts A by 3 bits and outputs once per
+// round, with B and C recomputed from A every time.
+fn check_shape(prog: &[i64]) -> Result<()> {
+    crate::cpu::disassemble(prog)?;
+    if !prog.ends_with(&[3, 0]) {
+        return Err(eyre!(
+            "Program must end with jnz 0 to loop back to the start"
+        ));
+    }
 
-            let mut b = b;
-            b ^= 2;
-            let c = prev_a >> b;
-            b ^= c;
-            b ^= 3;
+    let body = prog[..prog.len() - 2].chunks(2);
+    let count = |op, imm: Option<i64>| {
+        body.clone()
+            .filter(|i| i[0] == op && imm.is_none_or(|imm| i[1] == imm))
+            .count()
+    };
+    if count(3, None) != 0 {
+        return Err(eyre!("Program may only jump at the end of the loop"));
+    }
+    if count(0, Some(3)) != 1 || count(0, None) != 1 {
+        return Err(eyre!("Program must change A only with a single adv 3"));
+    }
+    if count(5, None) != 1 {
+        return Err(eyre!("Program must output exactly once per loop"));
+    }
 
-            let (front, last) = output.split_at(output.len() - 1);
-            if (b % 8) == last[0] {
-                run_backwards(r, prev_a, front)
-            }
+    let mut written = [true, false, false];
+    for i in body {
+        let (op, imm) = (i[0], i[1]);
+        let combo = (matches!(op, 0 | 2 | 5 | 6 | 7) && (5..=6).contains(&imm)).then_some(imm - 4);
+        let reads = match op {
+            1 => vec![1],
+            4 => vec![1, 2],
+            _ => vec![],
+        };
+        if let Some(r) = reads
+            .into_iter()
+            .chain(combo)
+            .find(|&r| !written[r as usize])
+        {
+            return Err(eyre!(
+                "Register {} is read before it is set, so it carries over between loops",
+                ["A", "B", "C"][r as usize]
+            ));
+        }
+        match op {
+            1 | 2 | 4 | 6 => written[1] = true,
+            7 => written[2] = true,
+            _ => (),
         }
     }
 
-    let (_, _, _, prog) = input;
-    let mut r = vec![];
-    run_backwards(&mut r, 0, prog);
-    *r.iter().min().unwrap()
+    Ok(())
 }
 
-fn disas(prog: &[i64]) {
-    for instruction in prog.chunks(2) {
-        let op = instruction[0];
-        let imm = instruction[1];
-
-        fn combo(imm: i64) -> &'static str {
-            match imm {
-                0 => "0",
-                1 => "1",
-                2 => "2",
-                3 => "3",
-                4 => "A",
-                5 => "B",
-                6 => "C",
-                _ => unreachable!(),
+/// Smallest A for which the program prints itself.
+pub fn quine(input: &Input) -> Result<i64> {
+    let (_, b, c, prog) = input;
+    check_shape(prog)?;
+
+    // Each round consumes the low 3 bits of A, so the last output only depends on the highest
+    // bits. Build A from the top, keeping every value whose output matches the program's tail.
+    let mut candidates = vec![0];
+    for i in (0..prog.len()).rev() {
+        let mut matching = Vec::new();
+        for a in candidates
+            .iter()
+            .flat_map(|a| (0..8).map(move |d| a << 3 | d))
+        {
+            if run(a, *b, *c, prog)? == prog[i..] {
+                matching.push(a);
             }
         }
-
-        match op {
-            0 => println!("{}{} adv 2^{}", op, imm, combo(imm)),
-            1 => println!("{}{} bxl {}", op, imm, imm),
-            2 => println!("{}{} bst {}", op, imm, combo(imm)),
-            3 => println!("{}{} jnz {}", op, imm, imm),
-            4 => println!("{}{} bxc", op, imm),
-            5 => println!("{}{} out {}", op, imm, combo(imm)),
-            6 => println!("{}{} bdv 2^{}", op, imm, combo(imm)),
-            7 => println!("{}{} cdv 2^{}", op, imm, combo(imm)),
-            _ => unreachable!(),
-        }
+        candidates = matching;
     }
+
+    candidates
+        .into_iter()
+        .filter(|&a| a > 0)
+        .min()
+        .ok_or_else(|| eyre!("No value of A makes the program print itself"))
+}
+
+pub fn part2(input: &Input) -> Result<i64> {
+    quine(input)
 }
 
 #[test]
@@ -157,17 +146,28 @@ fn test() -> Result<()> {
         input.3.len()
     );
     println!("{:?}", input.3);
-    disas(&input.3);
+    for instr in crate::cpu::disassemble(&input.3)? {
+        println!("{}", instr);
+    }
 
-    assert_eq!(part1(&example), *"4,6,3,5,6,3,5,2,1,0");
-    println!("part1: {}", part1(&input));
+    assert_eq!(part1(&example)?, *"4,6,3,5,6,3,5,2,1,0");
+    println!("part1: {}", part1(&input)?);
 
     let example2 = parse(&read_file("input/day17/example2.txt")?)?;
     println!("{:?}", example2.3);
-    disas(&example2.3);
-    //assert_eq!(part2(&example2), 117440);
-
-    println!("part2: {}", part2(&input));
+    for instr in crate::cpu::disassemble(&example2.3)? {
+        println!("{}", instr);
+    }
+    assert_eq!(part2(&example2)?, 117440);
+    println!("part2: {}", part2(&input)?);
+
+    assert!(part2(&example).is_err());
+    let wrong = (0, 1, 0, vec![2, 4, 1, 5, 4, 5, 5, 5, 0, 3, 3, 0]);
+    assert!(quine(&wrong).is_err());
+    // bst 7 has no combo operand
+    let invalid = (0, 0, 0, vec![2, 7, 0, 3, 5, 4, 3, 0]);
+    assert!(quine(&invalid).is_err());
+    assert!(part1(&invalid).is_err());
 
     Ok(())
 }
@@ -1,6 +1,6 @@
 use eyre::{Report, Result};
-use std::{cmp::Reverse, collections::BinaryHeap};
 
+use crate::search;
 use crate::Grid;
 
 type Input = Vec<(i64, i64)>;
@@ -18,35 +18,24 @@ pub fn parse(input: &str) -> Result<Input> {
         .collect::<Result<Vec<(i64, i64)>>>()
 }
 
-fn p(input: &[(i64, i64)], size: usize) -> Output {
+fn shortest(input: &[(i64, i64)], size: usize) -> Option<i64> {
     let mut grid = Grid::new(vec![vec![false; size + 1]; size + 1]);
     for &(x, y) in input {
         *(grid.get_mut(x, y).unwrap()) = true;
     }
     assert!(grid.get(0, 0) == Some(false));
 
-    let mut visited = grid.map(|_, _, _| None);
-    let mut queue = BinaryHeap::new();
-
-    queue.push(Reverse((0, 0, 0)));
-    while let Some(Reverse((score, x, y))) = queue.pop() {
-        if visited.get(x, y) != Some(None) {
-            continue;
-        }
-
-        if grid.get(x, y) != Some(false) {
-            continue;
-        }
-
-        *(visited.get_mut(x, y).unwrap()) = Some(score);
-
-        queue.push(Reverse((score + 1, x + 1, y)));
-        queue.push(Reverse((score + 1, x - 1, y)));
-        queue.push(Reverse((score + 1, x, y + 1)));
-        queue.push(Reverse((score + 1, x, y - 1)));
-    }
+    let end = (size as i64, size as i64);
+    let neighbours = |&(x, y): &(i64, i64)| {
+        [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+            .into_iter()
+            .filter(|&(x, y)| grid.get(x, y) == Some(false))
+    };
+    search::bfs((0, 0), neighbours, |&p| p == end).distance()
+}
 
-    visited.get(size as i64, size as i64).unwrap().unwrap()
+fn p(input: &[(i64, i64)], size: usize) -> Output {
+    shortest(input, size).unwrap()
 }
 
 pub fn part1(input: &Input) -> Output {
@@ -54,40 +43,18 @@ pub fn part1(input: &Input) -> Output {
 }
 
 fn p2(input: &[(i64, i64)], size: usize) -> String {
-    for n in 1..input.len() {
-        let mut grid = Grid::new(vec![vec![false; size + 1]; size + 1]);
-        for &(x, y) in &input[0..=n] {
-            *(grid.get_mut(x, y).unwrap()) = true;
-        }
-        assert!(grid.get(0, 0) == Some(false));
-
-        let mut visited = grid.map(|_, _, _| None);
-        let mut queue = BinaryHeap::new();
-
-        queue.push(Reverse((0, 0, 0)));
-        while let Some(Reverse((score, x, y))) = queue.pop() {
-            if visited.get(x, y) != Some(None) {
-                continue;
-            }
-
-            if grid.get(x, y) != Some(false) {
-                continue;
-            }
-
-            *(visited.get_mut(x, y).unwrap()) = Some(score);
-
-            queue.push(Reverse((score + 1, x + 1, y)));
-            queue.push(Reverse((score + 1, x - 1, y)));
-            queue.push(Reverse((score + 1, x, y + 1)));
-            queue.push(Reverse((score + 1, x, y - 1)));
-        }
-
-        if visited.get(size as i64, size as i64).unwrap().is_none() {
-            return format!("{},{}", input[n].0, input[n].1);
+    // Once blocked the exit stays blocked, so the first byte that does it can be bisected
+    let (mut open, mut blocked) = (0, input.len() - 1);
+    while blocked - open > 1 {
+        let n = (open + blocked) / 2;
+        if shortest(&input[0..=n], size).is_some() {
+            open = n;
+        } else {
+            blocked = n;
         }
     }
 
-    unreachable!()
+    format!("{},{}", input[blocked].0, input[blocked].1)
 }
 
 pub fn part2(input: &Input) -> String {
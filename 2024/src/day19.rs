@@ -1,5 +1,6 @@
 use eyre::Result;
-use std::collections::HashMap;
+
+use crate::memo::Memo;
 
 type Input = (Vec<String>, Vec<String>);
 type Output = i64;
@@ -40,33 +41,24 @@ pub fn part1(input: &Input) -> Output {
 }
 
 pub fn part2(input: &Input) -> Output {
-    fn possible(cache: &mut HashMap<String, i64>, patterns: &[String], design: &str) -> i64 {
-        if let Some(r) = cache.get(design) {
-            *r
-        } else {
-            let r = if design.is_empty() {
-                1
-            } else {
-                patterns
-                    .iter()
-                    .map(|s| {
-                        design
-                            .strip_prefix(s)
-                            .map(|tail| possible(cache, patterns, tail))
-                            .unwrap_or(0)
-                    })
-                    .sum()
-            };
-            cache.insert(design.to_string(), r);
-            r
+    fn possible<'a>(memo: &mut Memo<&'a str, i64>, patterns: &[String], design: &'a str) -> i64 {
+        if design.is_empty() {
+            return 1;
         }
+        memo.get(design, |memo| {
+            patterns
+                .iter()
+                .filter_map(|s| design.strip_prefix(s.as_str()))
+                .map(|tail| possible(memo, patterns, tail))
+                .sum()
+        })
     }
 
-    let mut cache = HashMap::new();
+    let mut memo = Memo::new();
     input
         .1
         .iter()
-        .map(|design| possible(&mut cache, &input.0, design))
+        .map(|design| possible(&mut memo, &input.0, design))
         .sum()
 }
 
@@ -1,7 +1,7 @@
 use eyre::Result;
 use itertools::Itertools;
-use std::{cmp::Reverse, collections::BinaryHeap};
 
+use crate::search;
 use crate::Grid;
 
 type Input = Grid<char>;
@@ -13,28 +13,14 @@ pub fn parse(input: &str) -> Result<Input> {
 }
 
 fn distance(input: &Grid<bool>, p: Point) -> Grid<Option<i64>> {
-    let mut visited = input.map(|_, _, _| None);
-    let mut queue = BinaryHeap::new();
-
-    queue.push(Reverse((0, p.0, p.1)));
-    while let Some(Reverse((score, x, y))) = queue.pop() {
-        if visited.get(x, y) != Some(None) {
-            continue;
-        }
-
-        if input.get(x, y) != Some(false) {
-            continue;
-        }
-
-        *(visited.get_mut(x, y).unwrap()) = Some(score);
-
-        queue.push(Reverse((score + 1, x + 1, y)));
-        queue.push(Reverse((score + 1, x - 1, y)));
-        queue.push(Reverse((score + 1, x, y + 1)));
-        queue.push(Reverse((score + 1, x, y - 1)));
-    }
-
-    visited
+    let neighbours = |&(x, y): &Point| {
+        [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+            .into_iter()
+            .filter(|&(x, y)| input.get(x, y) == Some(false))
+    };
+    let dist = search::bfs(p, neighbours, |_| false).dist;
+
+    input.map(|x, y, _| dist.get(&(x, y)).copied())
 }
 
 fn solve(input: &Input, n: i64, m: i64) -> Output {
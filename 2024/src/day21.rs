@@ -1,8 +1,8 @@
+use crate::search;
 use crate::Grid;
-use eyre::Result;
+use eyre::{eyre, OptionExt, Result};
 use itertools::Itertools;
 use std::collections::HashMap;
-use std::{cmp::Reverse, collections::BinaryHeap};
 
 type Input = Vec<String>;
 type Output = i64;
@@ -12,91 +12,292 @@ pub fn parse(input: &str) -> Result<Input> {
 }
 
 pub fn part1(input: &Input) -> Output {
-    let arrowpad = Grid::from_str(" ^A\n<v>");
-    let numpad = Grid::from_str("789\n456\n123\n 0A");
-    let pads = [&arrowpad, &arrowpad, &numpad];
-
-    solve(&pads, input)
+    solve(2, input)
 }
 
 pub fn part2(input: &Input) -> Output {
-    let arrowpad = Grid::from_str(" ^A\n<v>");
-    let numpad = Grid::from_str("789\n456\n123\n 0A");
-    let mut pads = vec![&arrowpad; 25];
-    pads.push(&numpad);
-
-    solve(&pads, input)
+    solve(25, input)
 }
 
-fn solve(pads: &[&Grid<char>], input: &Input) -> Output {
+fn solve(depth: usize, input: &Input) -> Output {
+    let arrows = Keypad::arrows();
+    let numbers = Keypad::numbers();
+    let mut pads = vec![&arrows; depth];
+    pads.push(&numbers);
+    let robots = Robots::new(&pads).unwrap();
+
     input
         .iter()
         .map(|code| {
             let n = code.strip_suffix('A').unwrap().parse::<i64>().unwrap();
-            let l: i64 = seq_len(pads, code).try_into().unwrap();
+            let l = robots.len(code).unwrap();
             n * l
         })
         .sum()
 }
 
-fn seq_len(pads: &[&Grid<char>], code: &str) -> usize {
-    let mut cost = HashMap::new();
-    for a in "A<>^v".chars() {
-        for b in "A<>^v".chars() {
-            cost.insert((a, b), 1);
+/// Keys laid out on a grid, with spaces or short lines for the gaps. Arms start on the A key.
+#[derive(Debug, Clone)]
+pub struct Keypad {
+    grid: Grid<char>,
+}
+
+impl Keypad {
+    pub fn parse(layout: &str) -> Result<Self> {
+        let width = layout.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        if width == 0 {
+            return Err(eyre!("Empty keypad"));
         }
+        let rows = layout
+            .lines()
+            .map(|l| l.chars().pad_using(width, |_| ' ').collect())
+            .collect();
+        let pad = Keypad {
+            grid: Grid::new(rows),
+        };
+
+        if let Some((_, _, key)) = pad.keys().duplicates_by(|&(_, _, k)| k).next() {
+            return Err(eyre!("Key {} appears twice", key));
+        }
+        let start = pad.start().ok_or_eyre("Keypad has no A key")?;
+        let reachable = search::bfs(start, |&(x, y)| pad.steps(x, y).map(|(p, _)| p), |_| false);
+        if let Some((_, _, key)) = pad
+            .keys()
+            .find(|&(x, y, _)| !reachable.dist.contains_key(&(x, y)))
+        {
+            return Err(eyre!("Key {} can't be reached from A", key));
+        }
+        Ok(pad)
+    }
+
+    /// The directional keypad.
+    pub fn arrows() -> Self {
+        Keypad::parse(" ^A\n<v>").unwrap()
     }
 
-    for pad in pads {
-        let mut next = HashMap::new();
+    /// The numeric keypad on the door.
+    pub fn numbers() -> Self {
+        Keypad::parse("789\n456\n123\n 0A").unwrap()
+    }
 
-        for (startx, starty, i) in pad.iter().filter(|&(_, _, c)| c != ' ') {
-            let mut visited = HashMap::new();
-            let mut queue = BinaryHeap::new();
-            queue.push(Reverse((0, startx, starty, 'A')));
+    pub fn keys(&self) -> impl Iterator<Item = (i64, i64, char)> + '_ {
+        self.grid.iter().filter(|&(_, _, c)| c != ' ')
+    }
 
-            while let Some(Reverse((score, x, y, prev))) = queue.pop() {
-                if visited.contains_key(&(x, y, prev)) {
-                    continue;
-                }
+    pub fn key(&self, x: i64, y: i64) -> Option<char> {
+        self.grid.get(x, y).filter(|&c| c != ' ')
+    }
 
-                visited.insert((x, y, prev), score + cost[&(prev, 'A')]);
-
-                let mut step = |delta: (i64, i64), key: char| {
-                    if let Some(j) = pad.get(x + delta.0, y + delta.1) {
-                        if j != ' ' {
-                            queue.push(Reverse((
-                                score + cost[&(prev, key)],
-                                x + delta.0,
-                                y + delta.1,
-                                key,
-                            )));
-                        }
-                    }
+    fn start(&self) -> Option<(i64, i64)> {
+        self.keys()
+            .find(|&(_, _, c)| c == 'A')
+            .map(|(x, y, _)| (x, y))
+    }
+
+    // Moves to neighbouring keys, along with the arrow that makes them
+    fn steps(&self, x: i64, y: i64) -> impl Iterator<Item = ((i64, i64), char)> + '_ {
+        [((1, 0), '>'), ((-1, 0), '<'), ((0, 1), 'v'), ((0, -1), '^')]
+            .into_iter()
+            .map(move |((dx, dy), arrow)| ((x + dx, y + dy), arrow))
+            .filter(|&((x, y), _)| self.key(x, y).is_some())
+    }
+
+    // Can this pad steer another one
+    fn steers(&self) -> bool {
+        "A<>^v".chars().all(|c| self.keys().any(|(_, _, k)| k == c))
+    }
+}
+
+/// A chain of keypads, each operated by a robot arm that is steered from the pad before it.
+/// The first pad is steered by the person pressing keys, the last one types the code.
+pub struct Robots<'a> {
+    pads: Vec<&'a Keypad>,
+    // Per pad, the cheapest presses on the steering pad to go from one key to another and
+    // press it, along with the number of keys the person has to press for that
+    moves: Vec<HashMap<(char, char), (i64, String)>>,
+}
+
+impl<'a> Robots<'a> {
+    /// Work out the cheapest moves on every pad, which fails if a pad other than the last one
+    /// lacks the keys to steer the next.
+    pub fn new(pads: &[&'a Keypad]) -> Result<Self> {
+        if pads.is_empty() {
+            return Err(eyre!("No keypads"));
+        }
+        if let Some(i) = pads[..pads.len() - 1].iter().position(|pad| !pad.steers()) {
+            return Err(eyre!("Keypad {} can't steer another one", i + 1));
+        }
+
+        let mut cost = HashMap::new();
+        for a in "A<>^v".chars() {
+            for b in "A<>^v".chars() {
+                cost.insert((a, b), 1);
+            }
+        }
+
+        let mut moves = Vec::new();
+        for pad in pads {
+            let mut next = HashMap::<(char, char), (i64, String)>::new();
+
+            let cost_ref = &cost;
+            for (startx, starty, i) in pad.keys() {
+                // State is the position and the last key pressed on the pad controlling this one
+                let neighbours = |&(x, y, prev): &(i64, i64, char)| {
+                    pad.steps(x, y)
+                        .map(move |((x, y), key)| ((x, y, key), cost_ref[&(prev, key)]))
                 };
+                let search = search::dijkstra((startx, starty, 'A'), neighbours, |_| false);
 
-                step((1, 0), '>');
-                step((-1, 0), '<');
-                step((0, 1), 'v');
-                step((0, -1), '^');
+                for (&(x, y, prev), &score) in search.dist.iter() {
+                    let j = pad.key(x, y).unwrap();
+                    let score = score + cost[&(prev, 'A')];
+                    if next.get(&(i, j)).is_some_and(|&(best, _)| best <= score) {
+                        continue;
+                    }
+                    let path = search.path_to(&(x, y, prev)).unwrap();
+                    let keys = path[1..].iter().map(|&(_, _, key)| key).chain(['A']);
+                    next.insert((i, j), (score, keys.collect()));
+                }
             }
 
-            for (&(x, y, _), &cost) in visited.iter() {
-                let j = pad.get(x, y).unwrap();
-                let c = next.entry((i, j)).or_insert(cost);
-                *c = core::cmp::min(*c, cost);
+            cost = next.iter().map(|(&pair, &(c, _))| (pair, c)).collect();
+            moves.push(next);
+        }
+
+        Ok(Robots {
+            pads: pads.to_vec(),
+            moves,
+        })
+    }
+
+    /// Keys the person presses to make the arm on `pad` go from one key to another and press
+    /// it, with pads counted from 0.
+    pub fn cost(&self, pad: usize, from: char, to: char) -> Option<i64> {
+        Some(self.moves.get(pad)?.get(&(from, to))?.0)
+    }
+
+    /// Costs for every pair of keys on `pad`, one row per starting key.
+    pub fn matrix(&self, pad: usize) -> String {
+        let keys = self.pads[pad].keys().map(|(_, _, k)| k).collect::<Vec<_>>();
+        let width = self.moves[pad]
+            .values()
+            .map(|(c, _)| c.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        let mut out = format!(
+            " {}\n",
+            keys.iter().map(|k| format!(" {:>width$}", k)).join("")
+        );
+        for &from in &keys {
+            out.push(from);
+            for &to in &keys {
+                out.push_str(&format!(" {:>width$}", self.cost(pad, from, to).unwrap()));
             }
+            out.push('\n');
         }
+        out
+    }
 
-        cost = next;
+    /// Fewest keys the person has to press to type the code, if the last pad has its keys.
+    pub fn len(&self, code: &str) -> Option<i64> {
+        let last = self.moves.last().unwrap();
+        ['A']
+            .into_iter()
+            .chain(code.chars())
+            .tuple_windows()
+            .map(|pair| last.get(&pair).map(|&(c, _)| c))
+            .sum()
     }
 
-    ['A']
-        .into_iter()
-        .chain(code.chars())
-        .tuple_windows::<(_, _)>()
-        .map(|pair| cost[&pair])
-        .sum()
+    /// One of the cheapest sequences of keys pressed on pad `level` to type the code, where
+    /// level 0 is the person and level `n` the last pad. Expanded lazily, since with many
+    /// robots the sequence is far too long to keep around. Fails if the last pad can't type
+    /// the code. There are no levels past the last pad, those press nothing.
+    pub fn presses(&'a self, code: &'a str, level: usize) -> Result<Presses<'a>> {
+        if self.len(code).is_none() {
+            return Err(eyre!("Can't type {}", code));
+        }
+        let stack = if level <= self.pads.len() {
+            vec![(self.pads.len(), code, 0, 'A')]
+        } else {
+            Vec::new()
+        };
+        Ok(Presses {
+            robots: self,
+            level,
+            stack,
+        })
+    }
+
+    /// Press the keys at level 0 and return what the last pad typed, failing if any arm
+    /// leaves its pad or points at the gap.
+    pub fn replay(&self, keys: impl IntoIterator<Item = char>) -> Result<String> {
+        let mut arms = self
+            .pads
+            .iter()
+            .map(|pad| pad.start().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut typed = String::new();
+        for key in keys {
+            let mut key = key;
+            for (robot, (pad, (x, y))) in self.pads.iter().zip(arms.iter_mut()).enumerate() {
+                let (dx, dy) = match key {
+                    '>' => (1, 0),
+                    '<' => (-1, 0),
+                    'v' => (0, 1),
+                    '^' => (0, -1),
+                    'A' => {
+                        key = pad.key(*x, *y).unwrap();
+                        if robot == self.pads.len() - 1 {
+                            typed.push(key);
+                        }
+                        continue;
+                    }
+                    _ => return Err(eyre!("Unknown key {}", key)),
+                };
+                (*x, *y) = (*x + dx, *y + dy);
+                if pad.key(*x, *y).is_none() {
+                    return Err(eyre!("Robot {} left its keypad at {},{}", robot + 1, x, y));
+                }
+                break;
+            }
+        }
+        Ok(typed)
+    }
+}
+
+/// Iterator over the keys pressed at one level of a robot chain.
+pub struct Presses<'a> {
+    robots: &'a Robots<'a>,
+    level: usize,
+    // Keys pressed on a level, the next of them to expand and the key pressed before it
+    stack: Vec<(usize, &'a str, usize, char)>,
+}
+
+impl Iterator for Presses<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            let (level, keys, i, prev) = self.stack.last_mut()?;
+            let Some(key) = keys[*i..].chars().next() else {
+                self.stack.pop();
+                continue;
+            };
+            *i += key.len_utf8();
+            if *level == self.level {
+                return Some(key);
+            }
+
+            // The steering arm is back on A after every press on this level
+            let from = std::mem::replace(prev, key);
+            let level = *level - 1;
+            let keys = &self.robots.moves[level][&(from, key)].1;
+            self.stack.push((level, keys, 0, 'A'));
+        }
+    }
 }
 
 #[test]
@@ -109,6 +310,56 @@ fn test() -> Result<()> {
     println!("input size {} ", input.len());
 
     assert_eq!(part1(&example), 126384);
+
+    let arrows = Keypad::arrows();
+    let numbers = Keypad::numbers();
+    let robots = Robots::new(&[&arrows, &arrows, &numbers])?;
+    assert_eq!(robots.presses("029A", 3)?.collect::<String>(), "029A");
+    assert_eq!(robots.presses("029A", 2)?.count(), 12);
+    assert_eq!(robots.presses("029A", 4)?.count(), 0);
+    for code in &example {
+        let keys = robots.presses(code, 0)?.collect::<String>();
+        assert_eq!(Some(keys.len() as i64), robots.len(code));
+        assert_eq!(robots.replay(keys.chars())?, *code);
+    }
+    // Two steps left from A puts the first arm on the gap
+    assert!(robots.replay("<<".chars()).is_err());
+    assert_eq!(robots.len("12B"), None);
+    assert!(robots.presses("12B", 0).is_err());
+
+    // Pressing A on the directional pad once it steers nothing costs one key, a move twice
+    assert_eq!(robots.cost(0, 'A', 'A'), Some(1));
+    assert_eq!(robots.cost(0, 'A', '^'), Some(2));
+    assert_eq!(robots.cost(0, 'A', '<'), Some(4));
+    assert!(robots.matrix(0).starts_with("  ^ A < v >\n^ 1 2 "));
+
+    let mut pads = vec![&arrows; 25];
+    pads.push(&numbers);
+    let robots = Robots::new(&pads)?;
+    let mut short = vec![&arrows; 5];
+    short.push(&numbers);
+    assert_eq!(
+        Some(robots.presses("029A", 20)?.count() as i64),
+        Robots::new(&short)?.len("029A")
+    );
+    let keys = robots.presses("029A", 0)?.take(100_000).collect::<String>();
+    assert_eq!(keys.len(), 100_000);
+
+    // A pad with a hole in the middle, steered by hand or through a robot
+    let ring = Keypad::parse("123\n4 5\n67A")?;
+    assert_eq!(Robots::new(&[&ring])?.len("4A"), Some(8));
+    let robots = Robots::new(&[&arrows, &ring])?;
+    let keys = robots.presses("4A", 0)?.collect::<String>();
+    assert_eq!(robots.replay(keys.chars())?, "4A");
+    assert_eq!(Some(keys.len() as i64), robots.len("4A"));
+
+    assert!(Keypad::parse("12\n3").is_err());
+    assert_eq!(Keypad::parse("A1\n2")?.keys().count(), 3);
+    assert!(Keypad::parse("").is_err());
+    assert!(Keypad::parse("1A\n1 ").is_err());
+    assert!(Keypad::parse("A \n 1").is_err());
+    assert!(Robots::new(&[&numbers, &numbers]).is_err());
+
     println!("part1: {}", part1(&input));
     println!("part2: {}", part2(&input));
 
@@ -1,7 +1,6 @@
 use eyre::{OptionExt, Result};
-use petgraph::prelude::*;
-use std::collections::HashSet;
-use string_interner::{symbol::SymbolU32, StringInterner};
+
+use crate::graph::Graph;
 
 type Input = Vec<(String, String)>;
 type Output = i64;
@@ -19,93 +18,28 @@ pub fn parse(input: &str) -> Result<Input> {
         .collect::<Result<_>>()
 }
 
-pub fn part1(input: &Input) -> Output {
-    let mut g = UnGraphMap::new();
-    let mut intern = StringInterner::default();
-
-    for (a, b) in input {
-        let a = intern.get_or_intern(a);
-        let b = intern.get_or_intern(b);
-        g.add_edge(a, b, ());
-    }
-
-    let mut cycles = HashSet::new();
-    for n1 in g.nodes() {
-        let name = intern.resolve(n1).unwrap();
-        if name.starts_with('t') {
-            for n2 in g.neighbors(n1) {
-                for n3 in g.neighbors(n2) {
-                    if g.contains_edge(n3, n1) {
-                        let mut cycle = vec![n1, n2, n3];
-                        cycle.sort();
-                        cycles.insert(cycle);
-                    }
-                }
-            }
-        }
-    }
-
-    cycles.len().try_into().unwrap()
+fn graph(input: &Input) -> Graph {
+    input
+        .iter()
+        .map(|(a, b)| (a.as_str(), b.as_str()))
+        .collect()
 }
 
-fn grow(
-    g: &UnGraphMap<SymbolU32, ()>,
-    set: &HashSet<SymbolU32>,
-    neighbors: &[SymbolU32],
-    best: &mut HashSet<SymbolU32>,
-) {
-    if set.len() + neighbors.len() <= best.len() {
-        // Cannot be better, early out
-    } else if neighbors.is_empty() {
-        // Base case
-        if set.len() > best.len() {
-            // Better
-            best.clone_from(set);
-        }
-    } else {
-        // Try skip
-        grow(g, set, &neighbors[1..], best);
+pub fn part1(input: &Input) -> Output {
+    let g = graph(input);
 
-        // Try add
-        let n = neighbors[0];
-        let c = g.neighbors(n).filter(|x| set.contains(x)).count();
-        if set.len() == c {
-            let mut set = set.clone();
-            set.insert(n);
-            grow(g, &set, &neighbors[1..], best);
-        }
-    }
+    g.triangles()
+        .iter()
+        .filter(|t| t.iter().any(|&n| g.name(n).starts_with('t')))
+        .count()
+        .try_into()
+        .unwrap()
 }
 
 pub fn part2(input: &Input) -> String {
-    let mut g = UnGraphMap::new();
-    let mut intern = StringInterner::default();
-
-    for (a, b) in input {
-        let a = intern.get_or_intern(a);
-        let b = intern.get_or_intern(b);
-        g.add_edge(a, b, ());
-    }
-
-    let mut best = HashSet::new();
-    for n in g.nodes() {
-        let mut set = HashSet::new();
-        set.insert(n);
-        let neighbors = g.neighbors(n).filter(|y| *y < n).collect::<Vec<_>>();
-        grow(&g, &set, &neighbors, &mut best);
-    }
+    let g = graph(input);
 
-    let mut cluster = best
-        .iter()
-        .map(|x| intern.resolve(*x).unwrap().to_string())
-        .collect::<Vec<_>>();
-
-    cluster.sort();
-
-    cluster
-        .into_iter()
-        .reduce(|a, b| format!("{},{}", a, b))
-        .unwrap()
+    g.names(&g.maximum_clique()).join(",")
 }
 
 #[test]
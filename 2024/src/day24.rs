@@ -1,6 +1,8 @@
-use eyre::{OptionExt, Report, Result};
+use eyre::{eyre, OptionExt, Report, Result};
 use std::collections::HashMap;
 
+use crate::circuit::Circuit;
+
 type Input = (Vec<(String, bool)>, Vec<(String, String, String, String)>);
 type Output = i64;
 
@@ -39,66 +41,176 @@ pub fn parse(input: &str) -> Result<Input> {
     Ok((inputs, gates))
 }
 
-fn lookup(
-    values: &mut HashMap<String, bool>,
-    outputs: &HashMap<String, (String, String, String)>,
-    id: &str,
-) -> bool {
-    if let Some(x) = values.get(id) {
-        *x
-    } else if let Some((op, a, b)) = outputs.get(id) {
-        let a = lookup(values, outputs, a);
-        let b = lookup(values, outputs, b);
-        let x = match op.as_str() {
-            "AND" => a && b,
-            "OR" => a || b,
-            "XOR" => a ^ b,
-            _ => unreachable!(),
-        };
-        values.insert(id.to_string(), x);
-        x
-    } else {
-        unreachable!()
+fn compile(outputs: &Netlist) -> Result<Circuit> {
+    Circuit::compile(
+        outputs
+            .iter()
+            .map(|(out, (op, a, b))| (a.as_str(), op.as_str(), b.as_str(), out.as_str())),
+    )
+}
+
+/// The puzzle's gates as a compiled circuit.
+pub fn circuit(input: &Input) -> Result<Circuit> {
+    compile(&netlist(input))
+}
+
+pub fn part1(input: &Input) -> Result<Output> {
+    let circuit = circuit(input)?;
+
+    // Inputs no gate reads aren't in the circuit, but a z among them still counts
+    let mut values = vec![0; circuit.wires()];
+    let mut unread = Vec::new();
+    for (name, v) in &input.0 {
+        match circuit.wire(name) {
+            Some(w) => values[w] = u64::from(*v),
+            None => unread.push((name.as_str(), i64::from(*v))),
+        }
     }
+    circuit.eval(&mut values);
+
+    Ok((0..circuit.wires())
+        .map(|w| (circuit.name(w), values[w] as i64))
+        .chain(unread)
+        .filter_map(|(name, v)| Some(v << name.strip_prefix('z')?.parse::<i64>().ok()?))
+        .sum())
 }
 
-pub fn part1(input: &Input) -> Output {
-    let mut values = HashMap::from_iter(input.0.iter().cloned());
-    let outputs = HashMap::from_iter(
+type Netlist = HashMap<String, (String, String, String)>;
+
+fn netlist(input: &Input) -> Netlist {
+    HashMap::from_iter(
         input
             .1
             .iter()
             .cloned()
             .map(|(a, op, b, out)| (out, (op, a, b))),
-    );
+    )
+}
 
-    let mut zs = input
-        .0
+fn wire(c: char, i: usize) -> String {
+    format!("{}{:02}", c, i)
+}
+
+// Output of the gate combining `a` and `b` with `op`
+fn find(outputs: &Netlist, op: &str, a: &str, b: &str) -> Option<String> {
+    outputs.iter().find_map(|(out, (o, x, y))| {
+        (o == op && ((x == a && y == b) || (x == b && y == a))).then(|| out.clone())
+    })
+}
+
+// Like find, but the gate has to be there
+fn gate(outputs: &Netlist, op: &str, a: &str, b: &str) -> Result<String> {
+    find(outputs, op, a, b).ok_or_else(|| eyre!("No {} gate reads {} and {}", op, a, b))
+}
+
+// The other input and output of a gate with `op` that has `a` as one input
+fn find_with(outputs: &Netlist, op: &str, a: &str) -> Option<(String, String)> {
+    outputs
         .iter()
-        .map(|(s, _)| s)
-        .filter(|s| s.starts_with('z'))
-        .cloned()
-        .collect::<Vec<_>>();
-    zs.extend(
-        input
-            .1
-            .iter()
-            .map(|(_, _, _, s)| s)
-            .filter(|s| s.starts_with('z'))
-            .cloned(),
-    );
-
-    zs.iter()
-        .map(|z| {
-            let v: i64 = lookup(&mut values, &outputs, z).into();
-            let p = z.strip_prefix('z').unwrap().parse::<i64>().unwrap();
-            v << p
+        .find_map(|(out, (o, x, y))| match (o == op, x == a, y == a) {
+            (true, true, _) => Some((y.clone(), out.clone())),
+            (true, _, true) => Some((x.clone(), out.clone())),
+            _ => None,
         })
-        .sum()
 }
 
-pub fn part2(_input: &Input) -> String {
-    "".to_string()
+fn swap(outputs: &mut Netlist, swapped: &mut Vec<String>, a: &str, b: &str) -> Result<()> {
+    if !outputs.contains_key(a) || !outputs.contains_key(b) {
+        return Err(eyre!(
+            "Can't swap {} and {}, both need to be gate outputs",
+            a,
+            b
+        ));
+    }
+    let ga = outputs.remove(a).unwrap();
+    let gb = outputs.insert(b.to_string(), ga).unwrap();
+    outputs.insert(a.to_string(), gb);
+    swapped.push(a.to_string());
+    swapped.push(b.to_string());
+    Ok(())
+}
+
+/// Walk the ripple-carry adder one bit at a time. Bit i should be
+/// `z = (x ^ y) ^ carry` and `carry' = ((x ^ y) & carry) | (x & y)`, wherever a gate isn't
+/// wired up that way, swap the outputs that make it so. Fails on gates missing for a swap to fix.
+fn repair(outputs: &mut Netlist, bits: usize) -> Result<Vec<String>> {
+    let mut swapped = Vec::new();
+
+    let sum = gate(outputs, "XOR", "x00", "y00")?;
+    if sum != "z00" {
+        swap(outputs, &mut swapped, &sum, "z00")?;
+    }
+    let mut carry = gate(outputs, "AND", "x00", "y00")?;
+
+    for i in 1..bits {
+        let (x, y, z) = (wire('x', i), wire('y', i), wire('z', i));
+        let half = |outputs: &Netlist| gate(outputs, "XOR", &x, &y);
+
+        if find(outputs, "XOR", &half(outputs)?, &carry).is_none() {
+            if let Some((other, _)) = find_with(outputs, "XOR", &half(outputs)?) {
+                swap(outputs, &mut swapped, &carry, &other)?;
+                carry = other;
+            } else {
+                let (other, _) = find_with(outputs, "XOR", &carry)
+                    .ok_or_else(|| eyre!("No sum gate for {} reads {}", z, carry))?;
+                swap(outputs, &mut swapped, &half(outputs)?, &other)?;
+            }
+        }
+
+        let sum = gate(outputs, "XOR", &half(outputs)?, &carry)?;
+        if sum != z {
+            swap(outputs, &mut swapped, &sum, &z)?;
+        }
+
+        let and = gate(outputs, "AND", &x, &y)?;
+        let through = gate(outputs, "AND", &half(outputs)?, &carry)?;
+        carry = gate(outputs, "OR", &through, &and)?;
+    }
+
+    let last = wire('z', bits);
+    if carry != last {
+        swap(outputs, &mut swapped, &carry, &last)?;
+    }
+
+    swapped.sort();
+    Ok(swapped)
+}
+
+// Simulate additions of pseudo random numbers
+fn adds(outputs: &Netlist, bits: usize) -> bool {
+    let Ok(circuit) = compile(outputs) else {
+        return false;
+    };
+
+    let mut seed = 0x2024_u64;
+    let mut random = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed & ((1 << bits) - 1)) as i64
+    };
+    let x = (0..256).map(|_| random()).collect::<Vec<_>>();
+    let y = (0..256).map(|_| random()).collect::<Vec<_>>();
+
+    let z = circuit.simulate(&[('x', &x), ('y', &y)], 'z');
+    z.iter()
+        .zip(x.iter().zip(&y))
+        .all(|(z, (x, y))| *z == x + y)
+}
+
+pub fn part2(input: &Input) -> Result<String> {
+    let mut outputs = netlist(input);
+    let bits = input.0.iter().filter(|(s, _)| s.starts_with('x')).count();
+
+    let swapped = repair(&mut outputs, bits)?;
+    if !adds(&outputs, bits) {
+        return Err(eyre!(
+            "Swapping {} doesn't make an adder",
+            swapped.join(",")
+        ));
+    }
+
+    Ok(swapped.join(","))
 }
 
 #[test]
@@ -110,10 +222,14 @@ fn test() -> Result<()> {
     let input = parse(&read_file("input/day24/input.txt")?)?;
     println!("input size {} {}", input.0.len(), input.1.len());
 
-    assert_eq!(part1(&example), 2024);
-    println!("part1: {}", part1(&input));
-    assert_eq!(part2(&example), "z00,z01,z02,z05");
-    println!("part2: {}", part2(&input));
+    assert_eq!(part1(&example)?, 2024);
+    println!("part1: {}", part1(&input)?);
+    let looped = parse("x00: 1\n\nx00 AND a -> b\nx00 OR b -> a\n")?;
+    assert!(part1(&looped).is_err());
+    // The example isn't an adder, check the input really was broken instead
+    assert!(!adds(&netlist(&input), 45));
+    println!("part2: {}", part2(&input)?);
+    assert!(part2(&example).is_err());
 
     Ok(())
 }
@@ -0,0 +1,194 @@
+#![allow(dead_code)]
+
+use petgraph::prelude::*;
+use std::collections::HashSet;
+use string_interner::{symbol::SymbolU32, DefaultStringInterner};
+
+pub type Node = SymbolU32;
+
+/// Undirected graph whose nodes are named by strings.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    pub g: UnGraphMap<Node, ()>,
+    intern: DefaultStringInterner,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_edge(&mut self, a: &str, b: &str) {
+        let a = self.intern.get_or_intern(a);
+        let b = self.intern.get_or_intern(b);
+        self.g.add_edge(a, b, ());
+    }
+
+    pub fn node(&self, name: &str) -> Option<Node> {
+        self.intern.get(name)
+    }
+
+    pub fn name(&self, node: Node) -> &str {
+        self.intern.resolve(node).unwrap()
+    }
+
+    /// Names of the nodes in alphabetical order.
+    pub fn names(&self, nodes: &[Node]) -> Vec<&str> {
+        let mut names = nodes.iter().map(|&n| self.name(n)).collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    fn adjacent(&self, n: Node) -> HashSet<Node> {
+        self.g.neighbors(n).collect()
+    }
+
+    /// Every triangle once, its nodes in increasing order.
+    pub fn triangles(&self) -> Vec<[Node; 3]> {
+        let mut triangles = Vec::new();
+        for a in self.g.nodes() {
+            for b in self.g.neighbors(a).filter(|&b| b > a) {
+                for c in self.g.neighbors(b).filter(|&c| c > b) {
+                    if self.g.contains_edge(a, c) {
+                        triangles.push([a, b, c]);
+                    }
+                }
+            }
+        }
+        triangles
+    }
+
+    /// Every clique of exactly `k` nodes once, its nodes in increasing order.
+    pub fn k_cliques(&self, k: usize) -> Vec<Vec<Node>> {
+        fn extend(graph: &Graph, k: usize, clique: &mut Vec<Node>, out: &mut Vec<Vec<Node>>) {
+            if clique.len() == k {
+                out.push(clique.clone());
+                return;
+            }
+
+            let last = *clique.last().unwrap();
+            let candidates = graph
+                .g
+                .neighbors(last)
+                .filter(|&n| n > last && clique.iter().all(|&c| graph.g.contains_edge(c, n)))
+                .collect::<Vec<_>>();
+            for n in candidates {
+                clique.push(n);
+                extend(graph, k, clique, out);
+                clique.pop();
+            }
+        }
+
+        let mut out = Vec::new();
+        if k == 0 {
+            return out;
+        }
+        for n in self.g.nodes() {
+            extend(self, k, &mut vec![n], &mut out);
+        }
+        out
+    }
+
+    /// Every clique that can't be grown any further, by Bron–Kerbosch with pivoting.
+    pub fn maximal_cliques(&self) -> Vec<Vec<Node>> {
+        fn bron_kerbosch(
+            graph: &Graph,
+            r: &mut Vec<Node>,
+            mut p: HashSet<Node>,
+            mut x: HashSet<Node>,
+            out: &mut Vec<Vec<Node>>,
+        ) {
+            // Any maximal clique contains the pivot or one of its non-neighbours
+            let Some(pivot) = p
+                .union(&x)
+                .max_by_key(|&&u| graph.g.neighbors(u).filter(|v| p.contains(v)).count())
+                .copied()
+            else {
+                out.push(r.clone());
+                return;
+            };
+
+            let adjacent = graph.adjacent(pivot);
+            let candidates = p.difference(&adjacent).copied().collect::<Vec<_>>();
+            for v in candidates {
+                let neighbours = graph.adjacent(v);
+                r.push(v);
+                bron_kerbosch(
+                    graph,
+                    r,
+                    p.intersection(&neighbours).copied().collect(),
+                    x.intersection(&neighbours).copied().collect(),
+                    out,
+                );
+                r.pop();
+                p.remove(&v);
+                x.insert(v);
+            }
+        }
+
+        let mut out = Vec::new();
+        bron_kerbosch(
+            self,
+            &mut Vec::new(),
+            self.g.nodes().collect(),
+            HashSet::new(),
+            &mut out,
+        );
+        for clique in &mut out {
+            clique.sort();
+        }
+        out
+    }
+
+    /// A largest clique.
+    pub fn maximum_clique(&self) -> Vec<Node> {
+        self.maximal_cliques()
+            .into_iter()
+            .max_by_key(|c| c.len())
+            .unwrap_or_default()
+    }
+}
+
+impl<'a> FromIterator<(&'a str, &'a str)> for Graph {
+    fn from_iter<I: IntoIterator<Item = (&'a str, &'a str)>>(iter: I) -> Self {
+        let mut graph = Graph::new();
+        for (a, b) in iter {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+}
+
+#[test]
+fn test() {
+    // A 4-clique and a triangle sharing the edge c-d, a tail e-f and a separate edge g-h
+    let graph = Graph::from_iter([
+        ("a", "b"),
+        ("a", "c"),
+        ("a", "d"),
+        ("b", "c"),
+        ("b", "d"),
+        ("c", "d"),
+        ("c", "e"),
+        ("d", "e"),
+        ("e", "f"),
+        ("g", "h"),
+    ]);
+
+    assert_eq!(graph.triangles().len(), 5);
+    assert_eq!(graph.k_cliques(2).len(), 10);
+    assert_eq!(graph.k_cliques(3).len(), 5);
+    assert_eq!(graph.k_cliques(4).len(), 1);
+    assert!(graph.k_cliques(5).is_empty());
+
+    let mut maximal = graph
+        .maximal_cliques()
+        .iter()
+        .map(|c| graph.names(c).join(","))
+        .collect::<Vec<_>>();
+    maximal.sort();
+    assert_eq!(maximal, ["a,b,c,d", "c,d,e", "e,f", "g,h"]);
+
+    assert_eq!(graph.names(&graph.maximum_clique()), ["a", "b", "c", "d"]);
+    assert_eq!(graph.name(graph.node("e").unwrap()), "e");
+}
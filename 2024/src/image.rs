@@ -0,0 +1,301 @@
+#![allow(dead_code)]
+
+use colored::Color;
+use eyre::{eyre, Result};
+use std::fmt::Write;
+
+use crate::{anim::Frame, sparse::SparseGrid, Grid};
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+impl<T: Copy> Grid<T> {
+    /// Plain PBM bitmap, `on` cells are black.
+    pub fn to_pbm(&self, on: impl Fn(T) -> bool) -> Vec<u8> {
+        let (w, h) = self.size();
+        let mut out = format!("P1\n{} {}\n", w, h);
+        for y in 0..h {
+            let row = (0..w).map(|x| {
+                if on(self.get(x, y).unwrap()) {
+                    "1"
+                } else {
+                    "0"
+                }
+            });
+            out.push_str(&row.collect::<Vec<_>>().join(" "));
+            out.push('\n');
+        }
+        out.into_bytes()
+    }
+
+    /// Binary PPM image with every cell drawn as a `scale` by `scale` square.
+    pub fn to_ppm(&self, color: impl Fn(T) -> Rgb, scale: usize) -> Vec<u8> {
+        let (w, h) = self.pixels(scale);
+        let mut out = format!("P6\n{} {}\n255\n", w, h).into_bytes();
+        out.extend(self.raster(color, scale).into_iter().flatten());
+        out
+    }
+
+    /// PNG image, stored without compression so it needs no deflate implementation. PNG has
+    /// no empty images, so an empty grid or a zero scale is an error.
+    pub fn to_png(&self, color: impl Fn(T) -> Rgb, scale: usize) -> Result<Vec<u8>> {
+        let (w, h) = self.pixels(scale);
+        if w == 0 || h == 0 {
+            return Err(eyre!("Can't write an empty {}x{} PNG", w, h));
+        }
+        let raster = self.raster(color, scale);
+
+        let mut scanlines = Vec::new();
+        for row in raster.chunks(w) {
+            scanlines.push(0); // No filter
+            scanlines.extend(row.iter().flatten());
+        }
+
+        let mut header = Vec::new();
+        header.extend((w as u32).to_be_bytes());
+        header.extend((h as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]); // 8 bit RGB, no interlace
+
+        let mut out = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        png_chunk(&mut out, b"IHDR", &header);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&scanlines));
+        png_chunk(&mut out, b"IEND", &[]);
+        Ok(out)
+    }
+
+    /// SVG image, cells mapped to `None` are left transparent.
+    pub fn to_svg(&self, color: impl Fn(T) -> Option<Rgb>, scale: usize) -> String {
+        let (w, h) = self.pixels(scale);
+        let mut out = svg_header(w, h);
+        out.push_str(&self.svg_rects(color, scale));
+        out.push_str("</svg>\n");
+        out
+    }
+
+    fn pixels(&self, scale: usize) -> (usize, usize) {
+        let (w, h) = self.size();
+        (w as usize * scale, h as usize * scale)
+    }
+
+    fn raster(&self, color: impl Fn(T) -> Rgb, scale: usize) -> Vec<Rgb> {
+        let (w, h) = self.size();
+        let mut raster = Vec::new();
+        for y in 0..h {
+            let row = (0..w)
+                .flat_map(|x| std::iter::repeat_n(color(self.get(x, y).unwrap()), scale))
+                .collect::<Vec<_>>();
+            for _ in 0..scale {
+                raster.extend(row.iter());
+            }
+        }
+        raster
+    }
+
+    // Runs of equal colour within a row share a single rect
+    fn svg_rects(&self, color: impl Fn(T) -> Option<Rgb>, scale: usize) -> String {
+        let (w, h) = self.size();
+        let mut out = String::new();
+        for y in 0..h {
+            let mut x = 0;
+            while x < w {
+                let c = color(self.get(x, y).unwrap());
+                let mut end = x + 1;
+                while end < w && color(self.get(end, y).unwrap()) == c {
+                    end += 1;
+                }
+                if let Some([r, g, b]) = c {
+                    writeln!(
+                        out,
+                        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#{:02x}{:02x}{:02x}"/>"##,
+                        x as usize * scale,
+                        y as usize * scale,
+                        (end - x) as usize * scale,
+                        scale,
+                        r,
+                        g,
+                        b
+                    )
+                    .unwrap();
+                }
+                x = end;
+            }
+        }
+        out
+    }
+}
+
+impl<T: Copy> SparseGrid<T> {
+    pub fn to_ppm(&self, color: impl Fn(T) -> Rgb, background: Rgb, scale: usize) -> Vec<u8> {
+        self.dense().to_ppm(|v| v.map_or(background, &color), scale)
+    }
+
+    pub fn to_png(
+        &self,
+        color: impl Fn(T) -> Rgb,
+        background: Rgb,
+        scale: usize,
+    ) -> Result<Vec<u8>> {
+        self.dense().to_png(|v| v.map_or(background, &color), scale)
+    }
+
+    pub fn to_svg(&self, color: impl Fn(T) -> Rgb, scale: usize) -> String {
+        self.dense().to_svg(|v| v.map(&color), scale)
+    }
+
+    fn dense(&self) -> Grid<Option<T>> {
+        SparseGrid::from_iter(self.iter().map(|(p, v)| (p, Some(v)))).to_grid(None)
+    }
+}
+
+/// Animated SVG that shows each frame for `delay` seconds and then loops.
+pub fn svg_animation<T: Copy>(
+    frames: &[Grid<T>],
+    color: impl Fn(T) -> Option<Rgb>,
+    scale: usize,
+    delay: f64,
+) -> String {
+    let (w, h) = frames
+        .iter()
+        .map(|f| f.pixels(scale))
+        .fold((0, 0), |a, b| (a.0.max(b.0), a.1.max(b.1)));
+    let total = delay * frames.len() as f64;
+    let n = frames.len() as f64;
+
+    let mut out = svg_header(w, h);
+    for (i, frame) in frames.iter().enumerate() {
+        let i = i as f64;
+        let (values, times) = if i == 0.0 {
+            ("inline;none", format!("0;{}", 1.0 / n))
+        } else {
+            ("none;inline;none", format!("0;{};{}", i / n, (i + 1.0) / n))
+        };
+        writeln!(
+            out,
+            r#"<g display="none"><animate attributeName="display" values="{}" keyTimes="{}" dur="{}s" calcMode="discrete" repeatCount="indefinite"/>"#,
+            values, times, total
+        )
+        .unwrap();
+        out.push_str(&frame.svg_rects(&color, scale));
+        out.push_str("</g>\n");
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Approximate RGB for the terminal colours used in animation frames.
+pub fn frame_rgb(frame: &Frame) -> Grid<Rgb> {
+    frame.map(|_, _, cell| match (cell.color, cell.c) {
+        (Some(color), _) => rgb(color),
+        (None, ' ' | '.') => BLACK,
+        (None, _) => [192, 192, 192],
+    })
+}
+
+fn rgb(color: Color) -> Rgb {
+    match color {
+        Color::Black => BLACK,
+        Color::Red => [205, 49, 49],
+        Color::Green => [13, 188, 121],
+        Color::Yellow => [229, 229, 16],
+        Color::Blue => [36, 114, 200],
+        Color::Magenta => [188, 63, 188],
+        Color::Cyan => [17, 168, 205],
+        Color::White => [229, 229, 229],
+        Color::BrightBlack => [102, 102, 102],
+        Color::BrightRed => [241, 76, 76],
+        Color::BrightGreen => [35, 209, 139],
+        Color::BrightYellow => [245, 245, 67],
+        Color::BrightBlue => [59, 142, 234],
+        Color::BrightMagenta => [214, 112, 214],
+        Color::BrightCyan => [41, 184, 219],
+        Color::BrightWhite => WHITE,
+        Color::TrueColor { r, g, b } => [r, g, b],
+    }
+}
+
+fn svg_header(w: usize, h: usize) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+        w, h, w, h
+    )
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none() as u8;
+        let len = block.len() as u16;
+        out.push(last);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &d| {
+        let a = (a + d as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    out.extend(((b << 16) | a).to_be_bytes());
+    out
+}
+
+#[test]
+fn test() {
+    let grid = Grid::from_str("#.\n.#");
+    assert_eq!(grid.to_pbm(|c| c == '#'), b"P1\n2 2\n1 0\n0 1\n");
+
+    let ppm = grid.to_ppm(|c| if c == '#' { WHITE } else { BLACK }, 2);
+    assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+    assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+
+    assert_eq!(crc32(b"IEND"), 0xae426082);
+    let png = grid.to_png(|_| WHITE, 1).unwrap();
+    assert!(png.starts_with(&[0x89, b'P', b'N', b'G']));
+    assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    assert!(grid.to_png(|_| WHITE, 0).is_err());
+    assert!(SparseGrid::<char>::new()
+        .to_png(|_| WHITE, BLACK, 1)
+        .is_err());
+
+    let svg = grid.to_svg(|c| (c == '#').then_some(WHITE), 10);
+    assert_eq!(svg.matches("<rect").count(), 2);
+    assert!(svg.contains(r##"<rect x="10" y="10" width="10" height="10" fill="#ffffff"/>"##));
+
+    let sparse = SparseGrid::from_iter([((-5, -5), 'a'), ((-3, -5), 'b')]);
+    let svg = sparse.to_svg(|_| BLACK, 1);
+    assert!(svg.contains(r#"width="3" height="1""#));
+    assert_eq!(svg.matches("<rect").count(), 2);
+
+    let animation = svg_animation(&[grid.clone(), grid], |_| Some(BLACK), 1, 0.5);
+    assert_eq!(animation.matches("<animate ").count(), 2);
+}
@@ -1,9 +1,12 @@
 use colored::*;
-use eyre::Result;
+use eyre::{eyre, Result};
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::prelude::*;
 
+mod anim;
+mod circuit;
+mod cpu;
 mod day1;
 mod day10;
 mod day11;
@@ -29,6 +32,13 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod disjoint;
+mod graph;
+mod image;
+mod memo;
+mod region;
+mod search;
+mod sparse;
 
 pub fn read_file(path: &str) -> Result<String> {
     let mut file = File::open(path)?;
@@ -51,6 +61,13 @@ impl<T: Copy> Grid<T> {
         Grid { size, data }
     }
 
+    pub fn with(x: i64, y: i64, v: T) -> Self {
+        Grid {
+            size: (x, y),
+            data: vec![v; (x * y) as usize],
+        }
+    }
+
     pub fn size(&self) -> (i64, i64) {
         self.size
     }
@@ -139,7 +156,82 @@ macro_rules! star {
     };
 }
 
+fn animation(day: &str) -> Result<Box<dyn Iterator<Item = anim::Frame>>> {
+    match day {
+        "14" => Ok(Box::new(day14::frames(day14::parse(&read_file(
+            "input/day14/input.txt",
+        )?)?))),
+        "15" => Ok(Box::new(day15::frames(day15::parse(&read_file(
+            "input/day15/input.txt",
+        )?)?))),
+        _ => Err(eyre!("No animation for day {}", day)),
+    }
+}
+
 fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["play", day] => return anim::play(animation(day)?.map(|f| anim::render(&f)), 30),
+        ["record", day, path] if path.ends_with(".svg") => {
+            let frames = animation(day)?
+                .map(|f| image::frame_rgb(&f))
+                .collect::<Vec<_>>();
+            return Ok(std::fs::write(
+                path,
+                image::svg_animation(&frames, Some, 4, 0.1),
+            )?);
+        }
+        ["record", day, path] => return anim::record(animation(day)?, path),
+        ["image", day, n, path] => {
+            let frame = animation(day)?
+                .nth(n.parse()?)
+                .ok_or_else(|| eyre!("No frame {}", n))?;
+            let rgb = image::frame_rgb(&frame);
+            let data = match path.rsplit('.').next() {
+                Some("pbm") => frame.to_pbm(|cell| cell.color.is_some()),
+                Some("ppm") => rgb.to_ppm(|c| c, 4),
+                Some("png") => rgb.to_png(|c| c, 4)?,
+                Some("svg") => rgb.to_svg(Some, 4).into_bytes(),
+                _ => return Err(eyre!("Unknown image format {}", path)),
+            };
+            return Ok(std::fs::write(path, data)?);
+        }
+        ["replay", path] => return anim::play(anim::load(path)?, 30),
+        ["debug", "17"] => {
+            let (a, b, c, prog) = day17::parse(&read_file("input/day17/input.txt")?)?;
+            let mut cpu = cpu::Cpu::new(a, b, c, &prog);
+            return cpu::debug(&mut cpu, std::io::stdin().lock(), std::io::stdout());
+        }
+        ["presses", "21", code] => {
+            let arrows = day21::Keypad::arrows();
+            let numbers = day21::Keypad::numbers();
+            let robots = day21::Robots::new(&[&arrows, &arrows, &numbers])?;
+            print!("{}", robots.matrix(2));
+            let mut keys = String::new();
+            for level in (0..=3).rev() {
+                keys = robots.presses(code, level)?.collect();
+                println!("{}: {}", level, keys);
+            }
+            println!("typed {}", robots.replay(keys.chars())?);
+            return Ok(());
+        }
+        ["netlist", "24", path] => {
+            let circuit = day24::circuit(&day24::parse(&read_file("input/day24/input.txt")?)?)?;
+            let data = match path.rsplit('.').next() {
+                Some("dot") => circuit.to_dot(),
+                Some("v") => circuit.to_verilog("day24"),
+                _ => return Err(eyre!("Unknown netlist format {}", path)),
+            };
+            return Ok(std::fs::write(path, data)?);
+        }
+        [] => (),
+        _ => {
+            return Err(eyre!(
+                "Usage: [play <day> | record <day> <file> | replay <file> | image <day> <frame> <file> | debug 17 | presses 21 <code> | netlist 24 <file>]"
+            ))
+        }
+    }
+
     {
         use day1 as day;
         let input = day::parse(&read_file("input/day1/input.txt")?)?;
@@ -269,7 +361,7 @@ fn main() -> Result<()> {
     {
         use day17 as day;
         let input = day::parse(&read_file("input/day17/input.txt")?)?;
-        star!(17, day::part1(&input), "3,1,4,3,1,7,1,6,3", day::part2(&input), 37221270076916);
+        star!(17, day::part1(&input)?, "3,1,4,3,1,7,1,6,3", day::part2(&input)?, 37221270076916);
     }
 
     {
@@ -337,10 +429,10 @@ fn main() -> Result<()> {
         let input = day::parse(&read_file("input/day24/input.txt")?)?;
         star!(
             24,
-            day::part1(&input),
+            day::part1(&input)?,
             58639252480880,
-            day::part2(&input),
-            *"todo"
+            day::part2(&input)?,
+            *"bkr,mqh,rnq,tfb,vvr,z08,z28,z39"
         );
     }
 
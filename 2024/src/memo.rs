@@ -0,0 +1,124 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// How often a `Memo` could answer from its cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hits, {} misses", self.hits, self.misses)
+    }
+}
+
+/// Cache for a recursive function. Keys may borrow, so slices of the input can be used
+/// directly, which also means a memo only lives as long as the call that created it.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    stats: Stats,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo {
+            cache: HashMap::new(),
+            stats: Stats::default(),
+        }
+    }
+
+    /// Cached value for `key`, otherwise computed by `f`, which gets the memo back to recurse.
+    pub fn get(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(v) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return v.clone();
+        }
+
+        self.stats.misses += 1;
+        let v = f(self);
+        self.cache.insert(key, v.clone());
+        v
+    }
+
+    /// Evaluate the recursive function `f` at `key`. Instead of calling itself `f` calls the
+    /// function it is given, which goes through the cache.
+    pub fn run(&mut self, key: K, f: &impl Fn(&mut dyn FnMut(K) -> V, K) -> V) -> V {
+        self.get(key.clone(), |memo| f(&mut |k| memo.run(k, f), key))
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Evaluate the recursive function `f` at `key` with a cache that is dropped afterwards.
+pub fn memoize<K: Hash + Eq + Clone, V: Clone>(
+    key: K,
+    f: impl Fn(&mut dyn FnMut(K) -> V, K) -> V,
+) -> V {
+    Memo::new().run(key, &f)
+}
+
+#[test]
+fn test() {
+    let fib = |fib: &mut dyn FnMut(u64) -> u64, n: u64| {
+        if n < 2 {
+            n
+        } else {
+            fib(n - 1) + fib(n - 2)
+        }
+    };
+    assert_eq!(memoize(90, fib), 2880067194370816120);
+
+    let mut memo = Memo::new();
+    assert_eq!(memo.run(10, &fib), 55);
+    assert_eq!(memo.len(), 11);
+    assert_eq!(
+        memo.stats(),
+        Stats {
+            hits: 8,
+            misses: 11
+        }
+    );
+    assert_eq!(memo.run(10, &fib), 55);
+    assert_eq!(memo.stats().hits, 9);
+
+    // Keys can borrow from data that outlives the memo
+    let words = ["a", "ab", "b", "ba"];
+    let text = "abab".to_string();
+    let mut memo = Memo::new();
+    fn split<'a>(memo: &mut Memo<&'a str, usize>, words: &[&str], text: &'a str) -> usize {
+        if text.is_empty() {
+            return 1;
+        }
+        memo.get(text, |memo| {
+            words
+                .iter()
+                .filter_map(|w| text.strip_prefix(w))
+                .map(|tail| split(memo, words, tail))
+                .sum()
+        })
+    }
+    assert_eq!(split(&mut memo, &words, &text), 5);
+    assert_eq!(memo.stats().to_string(), "2 hits, 4 misses");
+}
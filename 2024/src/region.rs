@@ -0,0 +1,168 @@
+#![allow(dead_code)]
+
+use crate::disjoint::DisjointSet;
+use crate::Grid;
+
+type Point = (i64, i64);
+
+static ORTHO: [Point; 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+static DIAG: [Point; 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+/// A 4-connected set of cells, labelled by `Grid::regions`.
+#[derive(Debug, Clone)]
+pub struct Region<T> {
+    pub label: usize,
+    pub value: T,
+    pub cells: Vec<Point>,
+    pub area: i64,
+    pub perimeter: i64,
+    /// Number of straight fence segments, which equals the number of corners.
+    pub sides: i64,
+    /// Inclusive (min, max) corners.
+    pub bbox: (Point, Point),
+    /// Enclosed pockets of other cells, counted with 8-connectivity.
+    pub holes: i64,
+}
+
+impl<T: Copy> Grid<T> {
+    /// Label every cell with a region id, joining orthogonal neighbours for which `same` holds.
+    pub fn label(&self, same: impl Fn(T, T) -> bool) -> (Grid<usize>, usize) {
+        let (w, h) = self.size();
+        let index = |x: i64, y: i64| (y * w + x) as usize;
+
+        let mut set = DisjointSet::new((w * h) as usize);
+        for (x, y, v) in self.iter() {
+            for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                if self.get(nx, ny).is_some_and(|n| same(v, n)) {
+                    set.union(index(x, y), index(nx, ny));
+                }
+            }
+        }
+
+        // Components come ordered by their first cell, so labels follow reading order
+        let components = set.components();
+        let mut labels = Grid::with(w, h, 0);
+        for (label, cells) in components.iter().enumerate() {
+            for &i in cells {
+                *labels.get_mut(i as i64 % w, i as i64 / w).unwrap() = label;
+            }
+        }
+
+        (labels, components.len())
+    }
+
+    /// Split the grid into regions and measure each of them.
+    pub fn regions(&self, same: impl Fn(T, T) -> bool) -> Vec<Region<T>> {
+        let (labels, count) = self.label(same);
+
+        let mut cells = vec![Vec::new(); count];
+        for (x, y, l) in labels.iter() {
+            cells[l].push((x, y));
+        }
+
+        cells
+            .into_iter()
+            .enumerate()
+            .map(|(label, cells)| {
+                let inside = |x, y| labels.get(x, y) == Some(label);
+                let (x0, y0) = cells[0];
+
+                let perimeter = cells
+                    .iter()
+                    .map(|&(x, y)| {
+                        ORTHO
+                            .iter()
+                            .filter(|(dx, dy)| !inside(x + dx, y + dy))
+                            .count() as i64
+                    })
+                    .sum();
+
+                let sides = cells
+                    .iter()
+                    .map(|&(x, y)| {
+                        DIAG.iter()
+                            .filter(|&&(dx, dy)| {
+                                let a = inside(x + dx, y);
+                                let b = inside(x, y + dy);
+                                let c = inside(x + dx, y + dy);
+                                (!a && !b) || (a && b && !c)
+                            })
+                            .count() as i64
+                    })
+                    .sum();
+
+                let bbox = cells
+                    .iter()
+                    .fold(((x0, y0), (x0, y0)), |(lo, hi), &(x, y)| {
+                        ((lo.0.min(x), lo.1.min(y)), (hi.0.max(x), hi.1.max(y)))
+                    });
+
+                Region {
+                    label,
+                    value: self.get(x0, y0).unwrap(),
+                    area: cells.len() as i64,
+                    perimeter,
+                    sides,
+                    bbox,
+                    holes: holes(bbox, inside),
+                    cells,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Count pockets of outside cells within the bounding box that can't reach its border.
+fn holes((lo, hi): (Point, Point), inside: impl Fn(i64, i64) -> bool) -> i64 {
+    let (lo, hi) = ((lo.0 - 1, lo.1 - 1), (hi.0 + 1, hi.1 + 1));
+    let mut seen = Grid::with(hi.0 - lo.0 + 1, hi.1 - lo.1 + 1, false);
+
+    let mut components = 0;
+    for y in lo.1..=hi.1 {
+        for x in lo.0..=hi.0 {
+            if inside(x, y) || seen.get(x - lo.0, y - lo.1) == Some(true) {
+                continue;
+            }
+
+            components += 1;
+            *seen.get_mut(x - lo.0, y - lo.1).unwrap() = true;
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                for (dx, dy) in ORTHO.iter().chain(DIAG.iter()) {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if inside(nx, ny) {
+                        continue;
+                    }
+                    if let Some(s) = seen.get_mut(nx - lo.0, ny - lo.1) {
+                        if !*s {
+                            *s = true;
+                            stack.push((nx, ny));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // The padded border is always one connected outside component
+    components - 1
+}
+
+#[test]
+fn test() {
+    let grid = Grid::from_str("AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA");
+    let regions = grid.regions(|a, b| a == b);
+    assert_eq!(regions.len(), 3);
+
+    let a = &regions[0];
+    assert_eq!((a.value, a.area, a.perimeter, a.sides), ('A', 28, 40, 12));
+    assert_eq!(a.bbox, ((0, 0), (5, 5)));
+    assert_eq!(a.holes, 1);
+
+    let b = &regions[1];
+    assert_eq!(
+        (b.value, b.area, b.perimeter, b.sides, b.holes),
+        ('B', 4, 8, 4, 0)
+    );
+    assert_eq!(b.bbox, ((3, 1), (4, 2)));
+}
@@ -0,0 +1,357 @@
+#![allow(dead_code)]
+
+use num::{BigUint, One};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Result of a search: the distance to every settled state and how it was reached.
+#[derive(Debug, Clone)]
+pub struct Search<S> {
+    pub dist: HashMap<S, i64>,
+    prev: HashMap<S, S>,
+    // Every predecessor on a shortest path, only filled in by the `_all` searches
+    preds: HashMap<S, Vec<S>>,
+    /// The goal state the search stopped at, if it found one.
+    pub goal: Option<S>,
+    /// All goal states at the shortest distance, just the one unless predecessors are kept.
+    pub goals: Vec<S>,
+}
+
+impl<S: Clone + Hash + Eq> Search<S> {
+    fn new() -> Self {
+        Search {
+            dist: HashMap::new(),
+            prev: HashMap::new(),
+            preds: HashMap::new(),
+            goal: None,
+            goals: Vec::new(),
+        }
+    }
+
+    /// Distance to the goal.
+    pub fn distance(&self) -> Option<i64> {
+        self.goal.as_ref().map(|g| self.dist[g])
+    }
+
+    pub fn distance_to(&self, s: &S) -> Option<i64> {
+        self.dist.get(s).copied()
+    }
+
+    /// States from the start to the goal, both included.
+    pub fn path(&self) -> Option<Vec<S>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+
+    pub fn path_to(&self, s: &S) -> Option<Vec<S>> {
+        self.dist.get(s)?;
+        let mut path = vec![s.clone()];
+        while let Some(p) = self.prev.get(path.last().unwrap()) {
+            path.push(p.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    fn parents(&self, s: &S) -> &[S] {
+        match (self.preds.get(s), self.prev.get(s)) {
+            (Some(preds), _) => preds,
+            (None, Some(prev)) => std::slice::from_ref(prev),
+            (None, None) => &[],
+        }
+    }
+
+    /// Every shortest path from the start to one of the goals, found lazily.
+    pub fn all_paths(&self) -> impl Iterator<Item = Vec<S>> + '_ {
+        let mut stack: Vec<Vec<S>> = self.goals.iter().map(|g| vec![g.clone()]).collect();
+        std::iter::from_fn(move || {
+            while let Some(path) = stack.pop() {
+                let parents = self.parents(path.last().unwrap());
+                if parents.is_empty() {
+                    return Some(path.into_iter().rev().collect());
+                }
+                for p in parents {
+                    let mut longer = path.clone();
+                    longer.push(p.clone());
+                    stack.push(longer);
+                }
+            }
+            None
+        })
+    }
+
+    /// States that lie on at least one shortest path to a goal.
+    pub fn on_any_path(&self) -> HashSet<S> {
+        let mut seen: HashSet<S> = self.goals.iter().cloned().collect();
+        let mut stack = self.goals.clone();
+        while let Some(s) = stack.pop() {
+            for p in self.parents(&s) {
+                if seen.insert(p.clone()) {
+                    stack.push(p.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Number of shortest paths, which can grow far beyond any machine integer. Parents are
+    /// counted before their children, which also holds for zero cost steps between states at
+    /// the same distance. A loop of zero cost steps would give infinitely many paths and panics.
+    pub fn count_paths(&self) -> BigUint {
+        let mut count: HashMap<S, BigUint> = HashMap::new();
+        // States whose parents are still being counted
+        let mut open = HashSet::new();
+        let mut stack: Vec<(S, bool)> = self.goals.iter().map(|g| (g.clone(), false)).collect();
+        while let Some((s, expanded)) = stack.pop() {
+            if count.contains_key(&s) {
+                continue;
+            }
+            let parents = self.parents(&s);
+            if expanded {
+                let n = if parents.is_empty() {
+                    BigUint::one()
+                } else {
+                    parents.iter().map(|p| &count[p]).sum()
+                };
+                open.remove(&s);
+                count.insert(s, n);
+            } else {
+                assert!(open.insert(s.clone()), "loop of zero cost steps");
+                stack.push((s.clone(), true));
+                stack.extend(parents.iter().map(|p| (p.clone(), false)));
+            }
+        }
+
+        self.goals.iter().map(|g| &count[g]).sum()
+    }
+}
+
+/// Breadth first search where every step costs 1. Stops at the first state for which `goal`
+/// holds, pass `|_| false` to get the distance to everything reachable.
+pub fn bfs<S, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Search<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+
+    search.dist.insert(start.clone(), 0);
+    queue.push_back(start);
+    while let Some(s) = queue.pop_front() {
+        if goal(&s) {
+            search.goal = Some(s.clone());
+            search.goals.push(s);
+            break;
+        }
+
+        let d = search.dist[&s];
+        for n in neighbours(&s) {
+            if !search.dist.contains_key(&n) {
+                search.dist.insert(n.clone(), d + 1);
+                search.prev.insert(n.clone(), s.clone());
+                queue.push_back(n);
+            }
+        }
+    }
+
+    search
+}
+
+/// Shortest paths with non-negative step costs, `neighbours` yields (state, cost) pairs.
+pub fn dijkstra<S, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Search<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = (S, i64)>,
+{
+    astar(start, neighbours, goal, |_| 0)
+}
+
+/// Dijkstra guided by a `heuristic` that must never overestimate the remaining distance, and
+/// must be consistent for the distances of states other than the goal to be exact.
+pub fn astar<S, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+    heuristic: impl FnMut(&S) -> i64,
+) -> Search<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = (S, i64)>,
+{
+    explore(start, neighbours, goal, heuristic, false)
+}
+
+/// Like `dijkstra`, but keeps every predecessor on a shortest path and every goal state at the
+/// shortest distance, so all the optimal paths can be recovered.
+pub fn dijkstra_all<S, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Search<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = (S, i64)>,
+{
+    explore(start, neighbours, goal, |_| 0, true)
+}
+
+/// Like `astar`, but keeps every optimal predecessor as `dijkstra_all` does.
+pub fn astar_all<S, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+    heuristic: impl FnMut(&S) -> i64,
+) -> Search<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = (S, i64)>,
+{
+    explore(start, neighbours, goal, heuristic, true)
+}
+
+fn explore<S, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+    mut heuristic: impl FnMut(&S) -> i64,
+    all: bool,
+) -> Search<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = (S, i64)>,
+{
+    let mut search = Search::new();
+    let mut best = HashMap::new();
+    // States aren't required to be Ord, so the queue holds indices into `open` instead
+    let mut open = vec![(start.clone(), None::<S>)];
+    let mut queue = BinaryHeap::new();
+
+    best.insert(start.clone(), 0);
+    queue.push(Reverse((heuristic(&start), 0, 0)));
+    while let Some(Reverse((f, d, i))) = queue.pop() {
+        if search.distance().is_some_and(|g| f > g) {
+            break;
+        }
+
+        let (s, prev) = std::mem::replace(&mut open[i], (start.clone(), None));
+        if search.dist.contains_key(&s) {
+            continue;
+        }
+
+        search.dist.insert(s.clone(), d);
+        if let Some(p) = prev {
+            search.prev.insert(s.clone(), p);
+        }
+        if goal(&s) {
+            search.goal.get_or_insert(s.clone());
+            search.goals.push(s);
+            if all {
+                continue;
+            }
+            break;
+        }
+
+        for (n, cost) in neighbours(&s) {
+            let d = d + cost;
+            match best.get(&n) {
+                Some(&b) if b < d => continue,
+                Some(&b) if b == d => {
+                    if all {
+                        search.preds.entry(n).or_default().push(s.clone());
+                    }
+                    continue;
+                }
+                _ => (),
+            }
+
+            best.insert(n.clone(), d);
+            if all {
+                search.preds.insert(n.clone(), vec![s.clone()]);
+            }
+            queue.push(Reverse((d + heuristic(&n), d, open.len())));
+            open.push((n, Some(s.clone())));
+        }
+    }
+
+    search
+}
+
+#[test]
+fn test() {
+    // Walk a 10x10 room with a wall at x = 5 that has a gap at y = 9
+    let open =
+        |&(x, y): &(i64, i64)| (0..10).contains(&x) && (0..10).contains(&y) && (x != 5 || y == 9);
+    let step = move |&(x, y): &(i64, i64)| {
+        [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+            .into_iter()
+            .filter(open)
+    };
+
+    let b = bfs((0, 0), step, |&p| p == (9, 0));
+    assert_eq!(b.distance(), Some(27));
+    let path = b.path().unwrap();
+    assert_eq!(path.len(), 28);
+    assert_eq!(path.first(), Some(&(0, 0)));
+    assert!(path.contains(&(5, 9)));
+
+    let weighted = |p: &(i64, i64)| step(p).map(|n| (n, 1));
+    let d = dijkstra((0, 0), weighted, |&p| p == (9, 0));
+    assert_eq!(d.distance(), Some(27));
+
+    let a = astar(
+        (0, 0),
+        weighted,
+        |&p| p == (9, 0),
+        |&(x, y)| (9 - x).abs() + y.abs(),
+    );
+    assert_eq!(a.distance(), Some(27));
+    assert!(a.dist.len() <= d.dist.len());
+
+    let all = bfs((0, 0), step, |_| false);
+    assert_eq!(all.goal, None);
+    assert_eq!(all.dist.len(), 90 + 1);
+    assert_eq!(all.distance_to(&(9, 0)), Some(27));
+    assert_eq!(all.path_to(&(5, 0)), None);
+
+    // Monotone lattice walks: C(2n, n) shortest paths from corner to corner
+    let lattice = |&(x, y): &(i64, i64)| {
+        [((x + 1, y), 1), ((x, y + 1), 1)]
+            .into_iter()
+            .filter(|&((x, y), _)| x <= 40 && y <= 40)
+    };
+    let small = dijkstra_all((0, 0), lattice, |&p| p == (2, 2));
+    assert_eq!(small.count_paths(), BigUint::from(6u32));
+    assert_eq!(small.all_paths().count(), 6);
+    assert!(small.all_paths().all(|p| p.len() == 5 && p[0] == (0, 0)));
+    assert_eq!(small.on_any_path().len(), 9);
+
+    let big = dijkstra_all((0, 0), lattice, |&p| p == (40, 40));
+    assert_eq!(big.count_paths().to_string(), "107507208733336176461620");
+
+    // Two goal states at the same distance are both kept
+    let ends = astar_all((0, 0), lattice, |&(x, y)| x + y == 2, |&(x, y)| 2 - x - y);
+    assert_eq!(ends.goals.len(), 3);
+    assert_eq!(ends.count_paths(), BigUint::from(4u32));
+    assert_eq!(d.count_paths(), BigUint::one());
+
+    // A chain of free steps next to a single free jump, so parents sit at the same distance
+    // as their children
+    let free = |&s: &i64| match s {
+        0 => vec![(1, 0), (20, 0)],
+        1..20 => vec![(s + 1, 0)],
+        20 => vec![(21, 1)],
+        _ => vec![],
+    };
+    let zero = dijkstra_all(0, free, |&s| s == 21);
+    assert_eq!(zero.distance(), Some(1));
+    assert_eq!(zero.count_paths(), BigUint::from(2u32));
+}
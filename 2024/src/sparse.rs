@@ -0,0 +1,74 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::Grid;
+
+type Point = (i64, i64);
+
+/// Cells at arbitrary, possibly negative, coordinates without storing the empty space between.
+#[derive(Debug, Clone, Default)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+}
+
+impl<T: Copy> SparseGrid<T> {
+    pub fn new() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, p: Point, v: T) -> Option<T> {
+        self.cells.insert(p, v)
+    }
+
+    pub fn remove(&mut self, p: Point) -> Option<T> {
+        self.cells.remove(&p)
+    }
+
+    pub fn get(&self, p: Point) -> Option<T> {
+        self.cells.get(&p).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, T)> + '_ {
+        self.cells.iter().map(|(p, v)| (*p, *v))
+    }
+
+    /// Inclusive (min, max) corners of the occupied cells.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let mut iter = self.cells.keys();
+        let first = *iter.next()?;
+        Some(iter.fold((first, first), |(lo, hi), &(x, y)| {
+            ((lo.0.min(x), lo.1.min(y)), (hi.0.max(x), hi.1.max(y)))
+        }))
+    }
+
+    /// Dense copy of the occupied bounding box, with `empty` in the gaps.
+    pub fn to_grid(&self, empty: T) -> Grid<T> {
+        let Some((lo, hi)) = self.bounds() else {
+            return Grid::with(0, 0, empty);
+        };
+        let mut grid = Grid::with(hi.0 - lo.0 + 1, hi.1 - lo.1 + 1, empty);
+        for (&(x, y), &v) in &self.cells {
+            *grid.get_mut(x - lo.0, y - lo.1).unwrap() = v;
+        }
+        grid
+    }
+}
+
+impl<T: Copy> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        SparseGrid {
+            cells: HashMap::from_iter(iter),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+#![allow(dead_code)]
+
+use crate::{grid::Grid, polygon::Polygon};
+
+type Point = (i64, i64);
+
+/// Cell boundaries along one axis. Every interesting coordinate gets a cell of its own and the
+/// gap up to the next one is collapsed into a single wide cell.
+#[derive(Debug, Clone)]
+struct Axis {
+    starts: Vec<i64>,
+    end: i64,
+}
+
+impl Axis {
+    fn new(coords: impl IntoIterator<Item = i64>) -> Self {
+        let mut coords = Vec::from_iter(coords);
+        coords.sort();
+        coords.dedup();
+        assert!(!coords.is_empty());
+
+        let mut starts = Vec::new();
+        for (i, &c) in coords.iter().enumerate() {
+            starts.push(c);
+            if coords.get(i + 1).is_some_and(|&n| n > c + 1) {
+                starts.push(c + 1);
+            }
+        }
+
+        Axis {
+            starts,
+            end: coords.last().unwrap() + 1,
+        }
+    }
+
+    fn len(&self) -> i64 {
+        self.starts.len() as i64
+    }
+
+    fn index(&self, c: i64) -> Option<i64> {
+        if c < self.starts[0] || c >= self.end {
+            return None;
+        }
+        Some(self.starts.partition_point(|&s| s <= c) as i64 - 1)
+    }
+
+    fn start(&self, i: i64) -> i64 {
+        self.starts[i as usize]
+    }
+
+    fn width(&self, i: i64) -> i64 {
+        self.starts.get(i as usize + 1).copied().unwrap_or(self.end) - self.start(i)
+    }
+}
+
+/// A `Grid` over a huge, sparse coordinate space where each cell stands for a block of tiles.
+#[derive(Debug, Clone)]
+pub struct CompressedGrid<T> {
+    xs: Axis,
+    ys: Axis,
+    grid: Grid<T>,
+}
+
+impl<T: Copy> CompressedGrid<T> {
+    pub fn new(xs: impl IntoIterator<Item = i64>, ys: impl IntoIterator<Item = i64>, v: T) -> Self {
+        let xs = Axis::new(xs);
+        let ys = Axis::new(ys);
+        let grid = Grid::with(xs.len(), ys.len(), v);
+        CompressedGrid { xs, ys, grid }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    pub fn grid_mut(&mut self) -> &mut Grid<T> {
+        &mut self.grid
+    }
+
+    /// Compressed cell containing the tile at `p`.
+    pub fn cell(&self, p: Point) -> Option<Point> {
+        Some((self.xs.index(p.0)?, self.ys.index(p.1)?))
+    }
+
+    /// First tile of a compressed cell, in original coordinates.
+    pub fn origin(&self, x: i64, y: i64) -> Point {
+        (self.xs.start(x), self.ys.start(y))
+    }
+
+    /// Number of tiles covered by a compressed cell.
+    pub fn weight(&self, x: i64, y: i64) -> i64 {
+        self.xs.width(x) * self.ys.width(y)
+    }
+
+    pub fn get(&self, p: Point) -> Option<T> {
+        let (x, y) = self.cell(p)?;
+        self.grid.get(x, y)
+    }
+
+    /// Set every cell in the inclusive tile rectangle spanned by `a` and `b`.
+    pub fn fill_rect(&mut self, a: Point, b: Point, v: T) {
+        let min = self.cell((a.0.min(b.0), a.1.min(b.1))).unwrap();
+        let max = self.cell((a.0.max(b.0), a.1.max(b.1))).unwrap();
+        for y in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                *self.grid.get_mut(x, y).unwrap() = v;
+            }
+        }
+    }
+
+    /// Set every cell whose tiles lie on or inside the polygon. Its vertices must be among the
+    /// interesting coordinates and its edges axis-aligned, so no cell is partially covered.
+    pub fn fill_polygon(&mut self, polygon: &Polygon, v: T) {
+        let (w, h) = self.grid.size();
+        let mut seen = Grid::with(w, h, false);
+
+        for (a, b) in polygon.edges() {
+            assert!(a.0 == b.0 || a.1 == b.1, "edges must be axis-aligned");
+            self.fill_rect(a, b, v);
+
+            let min = self.cell((a.0.min(b.0), a.1.min(b.1))).unwrap();
+            let max = self.cell((a.0.max(b.0), a.1.max(b.1))).unwrap();
+            for y in min.1..=max.1 {
+                for x in min.0..=max.0 {
+                    *seen.get_mut(x, y).unwrap() = true;
+                }
+            }
+        }
+
+        // Cells enclosed by the same edges are either all inside or all outside
+        for y in 0..h {
+            for x in 0..w {
+                if seen.get(x, y) != Some(false) {
+                    continue;
+                }
+
+                let inside = polygon.contains(self.origin(x, y));
+                *seen.get_mut(x, y).unwrap() = true;
+                let mut stack = vec![(x, y)];
+                while let Some((x, y)) = stack.pop() {
+                    if inside {
+                        *self.grid.get_mut(x, y).unwrap() = v;
+                    }
+                    for (dx, dy) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+                        if let Some(s) = seen.get_mut(x + dx, y + dy)
+                            && !*s
+                        {
+                            *s = true;
+                            stack.push((x + dx, y + dy));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Number of tiles in cells matching `pred`.
+    pub fn area(&self, pred: impl Fn(T) -> bool) -> i64 {
+        self.grid
+            .iter()
+            .filter(|&(_, _, v)| pred(v))
+            .map(|(x, y, _)| self.weight(x, y))
+            .sum()
+    }
+
+    /// Summed-area table of the tiles matching `pred`, for constant time rectangle queries.
+    pub fn area_table(&self, pred: impl Fn(T) -> bool) -> AreaTable {
+        let (w, h) = self.grid.size();
+        let mut sums = Grid::with(w + 1, h + 1, 0);
+        for y in 0..h {
+            for x in 0..w {
+                let own = if pred(self.grid.get(x, y).unwrap()) {
+                    self.weight(x, y)
+                } else {
+                    0
+                };
+                let sum = own + sums.get(x, y + 1).unwrap() + sums.get(x + 1, y).unwrap()
+                    - sums.get(x, y).unwrap();
+                *sums.get_mut(x + 1, y + 1).unwrap() = sum;
+            }
+        }
+
+        AreaTable {
+            xs: self.xs.clone(),
+            ys: self.ys.clone(),
+            sums,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AreaTable {
+    xs: Axis,
+    ys: Axis,
+    sums: Grid<i64>,
+}
+
+impl AreaTable {
+    /// Matching tiles in the cells covering the inclusive tile rectangle spanned by `a` and `b`.
+    pub fn rect(&self, a: Point, b: Point) -> i64 {
+        let x0 = self.xs.index(a.0.min(b.0)).unwrap();
+        let y0 = self.ys.index(a.1.min(b.1)).unwrap();
+        let x1 = self.xs.index(a.0.max(b.0)).unwrap() + 1;
+        let y1 = self.ys.index(a.1.max(b.1)).unwrap() + 1;
+
+        let s = |x, y| self.sums.get(x, y).unwrap();
+        s(x1, y1) - s(x0, y1) - s(x1, y0) + s(x0, y0)
+    }
+}
+
+#[test]
+fn test() {
+    let mut grid = CompressedGrid::new([0, 10, 1_000_000_000], [5, 6, 100], false);
+    assert_eq!(grid.grid().size(), (5, 4));
+    assert_eq!(grid.cell((500, 6)), Some((3, 1)));
+    assert_eq!(grid.cell((1_000_000_001, 6)), None);
+    assert_eq!(grid.weight(3, 3), 999_999_989);
+
+    grid.fill_rect((10, 5), (1_000_000_000, 6), true);
+    grid.fill_rect((0, 100), (0, 100), true);
+    assert_eq!(grid.area(|v| v), 999_999_991 * 2 + 1);
+
+    let table = grid.area_table(|v| v);
+    assert_eq!(table.rect((0, 5), (10, 100)), 3);
+    assert_eq!(table.rect((0, 5), (1_000_000_000, 100)), grid.area(|v| v));
+
+    let square = Polygon::new(vec![(0, 5), (10, 5), (10, 100), (0, 100)]);
+    grid.fill_polygon(&square, true);
+    assert_eq!(table.rect((0, 5), (10, 100)), 3);
+    assert_eq!(grid.area_table(|v| v).rect((0, 5), (10, 100)), 11 * 96);
+}
@@ -1,5 +1,6 @@
 use eyre::Result;
-use rangemap::RangeInclusiveSet;
+
+use crate::interval::IntervalSet;
 
 type Input = (Vec<(i64, i64)>, Vec<i64>);
 type Output = i64;
@@ -22,18 +23,15 @@ pub fn parse(input: &str) -> Result<Input> {
 }
 
 pub fn part1(input: &Input) -> Output {
-    let ranges = RangeInclusiveSet::from_iter(input.0.iter().map(|(a, b)| *a..=*b));
+    let ranges = IntervalSet::from_iter(input.0.iter().map(|(a, b)| *a..*b + 1));
 
-    input.1.iter().filter(|id| ranges.contains(id)).count() as i64
+    input.1.iter().filter(|id| ranges.contains(**id)).count() as i64
 }
 
 pub fn part2(input: &Input) -> Output {
-    let ranges = RangeInclusiveSet::from_iter(input.0.iter().map(|(a, b)| *a..=*b));
+    let ranges = IntervalSet::from_iter(input.0.iter().map(|(a, b)| *a..*b + 1));
 
-    ranges
-        .iter()
-        .map(|range| range.end() - range.start() + 1)
-        .sum()
+    ranges.length()
 }
 
 #[test]
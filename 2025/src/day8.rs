@@ -1,7 +1,8 @@
 use eyre::Result;
-use itertools::Itertools;
 use std::{array, cmp::Reverse, collections::BinaryHeap};
 
+use crate::disjoint::DisjointSet;
+
 type Input = Vec<[i64; 3]>;
 type Output = i64;
 
@@ -15,17 +16,6 @@ pub fn parse(input: &str) -> Result<Input> {
         .collect())
 }
 
-fn find(uf: &mut [usize], i: usize) -> usize {
-    let j = uf[i];
-    if j == i {
-        i
-    } else {
-        let id = find(uf, j);
-        uf[i] = id;
-        id
-    }
-}
-
 pub fn part1(input: &Input, n: usize) -> Output {
     let permutations = input.iter().enumerate().flat_map(|(i, x)| {
         input.iter().enumerate().skip(i + 1).map(move |(j, y)| {
@@ -40,22 +30,15 @@ pub fn part1(input: &Input, n: usize) -> Output {
     });
 
     let mut heap = BinaryHeap::from_iter(permutations);
-    let mut uf: Vec<usize> = input.iter().enumerate().map(|(i, _)| i).collect();
+    let mut circuits = DisjointSet::new(input.len());
 
     (0..n)
         .filter_map(|_| heap.pop())
         .for_each(|Reverse((_, i, j))| {
-            let a = find(&mut uf, i);
-            let b = find(&mut uf, j);
-            uf[a] = b;
+            circuits.union(i, j);
         });
 
-    let clusters = input
-        .iter()
-        .enumerate()
-        .map(|(i, _)| find(&mut uf, i))
-        .counts();
-    let mut big = BinaryHeap::from_iter(clusters.values().copied());
+    let mut big = BinaryHeap::from_iter(circuits.sizes());
 
     (0..3).filter_map(|_| big.pop()).product::<usize>() as i64
 }
@@ -75,23 +58,12 @@ pub fn part2(input: &Input) -> Output {
     });
 
     let mut heap = BinaryHeap::from_iter(permutations);
-    let mut uf: Vec<usize> = input.iter().enumerate().map(|(i, _)| i).collect();
-    let mut clusters = input.len();
+    let mut circuits = DisjointSet::new(input.len());
 
     (0..)
         .filter_map(|_| heap.pop())
         .find_map(|Reverse((_, i, j))| {
-            let a = find(&mut uf, i);
-            let b = find(&mut uf, j);
-            if a != b {
-                uf[a] = b;
-                clusters -= 1;
-                if clusters == 1 {
-                    return Some(input[i][0] * input[j][0]);
-                }
-            }
-
-            None
+            (circuits.union(i, j) && circuits.count() == 1).then(|| input[i][0] * input[j][0])
         })
         .unwrap()
 }
@@ -0,0 +1,122 @@
+#![allow(dead_code)]
+
+/// Union-find over the elements `0..n`, with path compression and union by size.
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    count: usize,
+}
+
+impl DisjointSet {
+    pub fn new(n: usize) -> Self {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            count: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Representative of the component containing `i`.
+    pub fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        let mut i = i;
+        while self.parent[i] != root {
+            i = std::mem::replace(&mut self.parent[i], root);
+        }
+
+        root
+    }
+
+    /// Join the components of `a` and `b`, false if they already were the same.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.count -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Number of elements in the component containing `i`.
+    pub fn size(&mut self, i: usize) -> usize {
+        let root = self.find(i);
+        self.size[root]
+    }
+
+    /// Number of components, kept up to date as they are joined.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Sizes of all the components.
+    pub fn sizes(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&i| self.parent[i] == i)
+            .map(|i| self.size[i])
+            .collect()
+    }
+
+    /// Elements of every component, components ordered by their smallest element.
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut index = vec![usize::MAX; self.len()];
+        let mut components: Vec<Vec<usize>> = Vec::new();
+        for i in 0..self.len() {
+            let root = self.find(i);
+            if index[root] == usize::MAX {
+                index[root] = components.len();
+                components.push(Vec::new());
+            }
+            components[index[root]].push(i);
+        }
+        components
+    }
+}
+
+#[test]
+fn test() {
+    let mut set = DisjointSet::new(8);
+    assert_eq!(set.count(), 8);
+
+    assert!(set.union(0, 1));
+    assert!(set.union(2, 3));
+    assert!(set.union(1, 3));
+    assert!(!set.union(0, 2));
+    assert!(set.union(5, 6));
+
+    assert_eq!(set.count(), 4);
+    assert!(set.same(0, 3));
+    assert!(!set.same(0, 5));
+    assert_eq!(set.size(2), 4);
+    assert_eq!(set.size(4), 1);
+
+    let mut sizes = set.sizes();
+    sizes.sort();
+    assert_eq!(sizes, [1, 1, 2, 4]);
+    assert_eq!(
+        set.components(),
+        [vec![0, 1, 2, 3], vec![4], vec![5, 6], vec![7]]
+    );
+}
@@ -0,0 +1,252 @@
+#![allow(dead_code)]
+
+use std::ops::Range;
+
+/// Set of integers stored as sorted, disjoint and non-touching half-open ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<Range<i64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    pub fn insert(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Ranges that overlap or touch the new one are absorbed into it
+        let lo = self.ranges.partition_point(|r| r.end < range.start);
+        let hi = self.ranges.partition_point(|r| r.start <= range.end);
+        let start = self.ranges[lo..hi]
+            .first()
+            .map_or(range.start, |r| r.start.min(range.start));
+        let end = self.ranges[lo..hi]
+            .last()
+            .map_or(range.end, |r| r.end.max(range.end));
+        self.ranges.splice(lo..hi, std::iter::once(start..end));
+    }
+
+    pub fn contains(&self, v: i64) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= v);
+        self.ranges.get(i).is_some_and(|r| r.start <= v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Range<i64>> + '_ {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of integers in the set.
+    pub fn length(&self) -> i64 {
+        self.ranges.iter().map(|r| r.end - r.start).sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.ranges.last().map(|r| r.end - 1)
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut set = self.clone();
+        for r in other.iter() {
+            set.insert(r.clone());
+        }
+        set
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let r = a.start.max(b.start)..a.end.min(b.end);
+            if !r.is_empty() {
+                ranges.push(r);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// Everything within `bounds` that isn't in the set.
+    pub fn complement(&self, bounds: Range<i64>) -> IntervalSet {
+        let mut ranges = Vec::new();
+        let mut start = bounds.start;
+        for r in self.ranges.iter() {
+            if r.start > start {
+                ranges.push(start..r.start.min(bounds.end));
+            }
+            start = start.max(r.end);
+        }
+        if start < bounds.end {
+            ranges.push(start..bounds.end);
+        }
+        ranges.retain(|r| !r.is_empty());
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        match (self.min(), self.max()) {
+            (Some(min), Some(max)) => self.intersection(&other.complement(min..max + 1)),
+            _ => IntervalSet::new(),
+        }
+    }
+}
+
+impl FromIterator<Range<i64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<i64>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for r in iter {
+            set.insert(r);
+        }
+        set
+    }
+}
+
+/// Piecewise translation of the integers. Each range is shifted by its own offset and values
+/// outside every range map to themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalMap {
+    pieces: Vec<(Range<i64>, i64)>,
+}
+
+impl IntervalMap {
+    pub fn new() -> Self {
+        IntervalMap { pieces: Vec::new() }
+    }
+
+    /// Shift `range` by `offset`, replacing whatever was mapped there before.
+    pub fn insert(&mut self, range: Range<i64>, offset: i64) {
+        if range.is_empty() {
+            return;
+        }
+
+        let lo = self.pieces.partition_point(|(r, _)| r.end <= range.start);
+        let hi = self.pieces.partition_point(|(r, _)| r.start < range.end);
+
+        let overlap = &self.pieces[lo..hi];
+        let mut replace = Vec::new();
+        if let Some((r, o)) = overlap.first().filter(|(r, _)| r.start < range.start) {
+            replace.push((r.start..range.start, *o));
+        }
+        replace.push((range.clone(), offset));
+        if let Some((r, o)) = overlap.last().filter(|(r, _)| r.end > range.end) {
+            replace.push((range.end..r.end, *o));
+        }
+
+        self.pieces.splice(lo..hi, replace);
+    }
+
+    pub fn offset(&self, v: i64) -> i64 {
+        let i = self.pieces.partition_point(|(r, _)| r.end <= v);
+        match self.pieces.get(i) {
+            Some((r, o)) if r.start <= v => *o,
+            _ => 0,
+        }
+    }
+
+    pub fn get(&self, v: i64) -> i64 {
+        v + self.offset(v)
+    }
+
+    /// Image of a whole set under the map.
+    pub fn apply(&self, set: &IntervalSet) -> IntervalSet {
+        let mut image = IntervalSet::new();
+        for range in set.iter() {
+            let mut start = range.start;
+            while start < range.end {
+                let i = self.pieces.partition_point(|(r, _)| r.end <= start);
+                let (end, offset) = match self.pieces.get(i) {
+                    Some((r, o)) if r.start <= start => (r.end.min(range.end), *o),
+                    Some((r, _)) => (r.start.min(range.end), 0),
+                    None => (range.end, 0),
+                };
+                image.insert(start + offset..end + offset);
+                start = end;
+            }
+        }
+        image
+    }
+
+    /// The map that applies `self` first and `next` after it.
+    pub fn then(&self, next: &IntervalMap) -> IntervalMap {
+        let mut cuts = Vec::new();
+        for (r, _) in self.pieces.iter() {
+            cuts.extend([r.start, r.end]);
+        }
+        for (r, _) in next.pieces.iter() {
+            for b in [r.start, r.end] {
+                // Every value that self sends to b
+                if self.offset(b) == 0 {
+                    cuts.push(b);
+                }
+                cuts.extend(
+                    self.pieces
+                        .iter()
+                        .filter(|(r, o)| r.contains(&(b - o)))
+                        .map(|(_, o)| b - o),
+                );
+            }
+        }
+        cuts.sort();
+        cuts.dedup();
+
+        let mut map = IntervalMap::new();
+        for w in cuts.windows(2) {
+            let offset = self.offset(w[0]) + next.offset(self.get(w[0]));
+            if offset == 0 {
+                continue;
+            }
+            match map.pieces.last_mut() {
+                Some((r, o)) if r.end == w[0] && *o == offset => r.end = w[1],
+                _ => map.pieces.push((w[0]..w[1], offset)),
+            }
+        }
+        map
+    }
+}
+
+#[test]
+fn test() {
+    let a = IntervalSet::from_iter([0..5, 10..20, 4..7, 20..22]);
+    assert_eq!(a.iter().cloned().collect::<Vec<_>>(), vec![0..7, 10..22]);
+    assert_eq!(a.length(), 19);
+    assert!(a.contains(6) && !a.contains(7) && a.contains(21));
+
+    let b = IntervalSet::from_iter([5..12, 30..40]);
+    assert_eq!(a.union(&b), IntervalSet::from_iter([0..22, 30..40]));
+    assert_eq!(a.intersection(&b), IntervalSet::from_iter([5..7, 10..12]));
+    assert_eq!(a.difference(&b), IntervalSet::from_iter([0..5, 12..22]));
+    assert_eq!(a.complement(-5..15), IntervalSet::from_iter([-5..0, 7..10]));
+
+    // 2023 day 5 example, seed-to-soil followed by soil-to-fertilizer
+    let mut soil = IntervalMap::new();
+    soil.insert(98..100, -48);
+    soil.insert(50..98, 2);
+    let mut fertilizer = IntervalMap::new();
+    fertilizer.insert(15..52, -15);
+    fertilizer.insert(52..54, -15);
+    fertilizer.insert(0..15, 39);
+
+    let both = soil.then(&fertilizer);
+    for seed in 0..110 {
+        assert_eq!(both.get(seed), fertilizer.get(soil.get(seed)));
+    }
+
+    let seeds = IntervalSet::from_iter([79..93, 55..68]);
+    assert_eq!(both.apply(&seeds), fertilizer.apply(&soil.apply(&seeds)));
+    assert_eq!(both.apply(&seeds).length(), seeds.length());
+}
@@ -3,6 +3,7 @@ use eyre::Result;
 use std::fs::File;
 use std::io::prelude::*;
 
+mod compressed;
 mod day1;
 mod day10;
 mod day11;
@@ -16,7 +17,11 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod disjoint;
 mod grid;
+mod interval;
+mod paths;
+mod polygon;
 
 pub fn read_file(path: &str) -> Result<String> {
     let mut file = File::open(path)?;
@@ -0,0 +1,309 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Directed graph stored as successor lists.
+#[derive(Debug, Clone)]
+pub struct Digraph<N> {
+    edges: HashMap<N, Vec<N>>,
+}
+
+/// Restrictions on the paths counted by `Digraph::count_paths`.
+#[derive(Debug, Clone)]
+pub struct Constraints<N> {
+    /// Nodes every path has to pass through, in any order.
+    pub must: Vec<N>,
+    /// Nodes no path may enter.
+    pub avoid: HashSet<N>,
+}
+
+impl<N> Default for Constraints<N> {
+    fn default() -> Self {
+        Constraints {
+            must: Vec::new(),
+            avoid: HashSet::new(),
+        }
+    }
+}
+
+impl<N: Copy + Hash + Eq> Digraph<N> {
+    pub fn new() -> Self {
+        Digraph {
+            edges: HashMap::new(),
+        }
+    }
+
+    pub fn add_edge(&mut self, a: N, b: N) {
+        self.edges.entry(a).or_default().push(b);
+    }
+
+    pub fn successors(&self, n: N) -> &[N] {
+        self.edges.get(&n).map_or(&[], Vec::as_slice)
+    }
+
+    /// Nodes reachable from `from` in topological order, or a cycle among them.
+    pub fn topological_order(&self, from: N) -> Result<Vec<N>, Vec<N>> {
+        let reachable = self.reachable(from, |n| self.successors(n).to_vec());
+        self.order_within(from, &reachable)
+            .ok_or_else(|| self.cycle(from).unwrap())
+    }
+
+    // Everything `next` leads to from `from`, itself included
+    fn reachable(&self, from: N, mut next: impl FnMut(N) -> Vec<N>) -> HashSet<N> {
+        let mut reachable = HashSet::from([from]);
+        let mut work = vec![from];
+        while let Some(n) = work.pop() {
+            for s in next(n) {
+                if reachable.insert(s) {
+                    work.push(s);
+                }
+            }
+        }
+        reachable
+    }
+
+    // Kahn's algorithm on the edges between `nodes`, None if they contain a cycle
+    fn order_within(&self, from: N, nodes: &HashSet<N>) -> Option<Vec<N>> {
+        let inside = |n: N| {
+            self.successors(n)
+                .iter()
+                .copied()
+                .filter(|s| nodes.contains(s))
+        };
+        let mut incoming = HashMap::<N, usize>::new();
+        for &n in nodes {
+            for s in inside(n) {
+                *incoming.entry(s).or_default() += 1;
+            }
+        }
+        if incoming.contains_key(&from) {
+            return None;
+        }
+
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(n) = queue.pop_front() {
+            order.push(n);
+            for s in inside(n) {
+                let count = incoming.get_mut(&s).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    queue.push_back(s);
+                }
+            }
+        }
+
+        (order.len() == nodes.len()).then_some(order)
+    }
+
+    /// Some cycle reachable from `from`.
+    pub fn cycle(&self, from: N) -> Option<Vec<N>> {
+        fn visit<N: Copy + Hash + Eq>(
+            graph: &Digraph<N>,
+            n: N,
+            path: &mut Vec<N>,
+            on_path: &mut HashSet<N>,
+            done: &mut HashSet<N>,
+        ) -> Option<Vec<N>> {
+            if on_path.contains(&n) {
+                let start = path.iter().position(|&p| p == n).unwrap();
+                return Some(path[start..].to_vec());
+            }
+            if !done.insert(n) {
+                return None;
+            }
+
+            path.push(n);
+            on_path.insert(n);
+            for &s in graph.successors(n) {
+                if let Some(cycle) = visit(graph, s, path, on_path, done) {
+                    return Some(cycle);
+                }
+            }
+            path.pop();
+            on_path.remove(&n);
+            None
+        }
+
+        visit(
+            self,
+            from,
+            &mut Vec::new(),
+            &mut HashSet::new(),
+            &mut HashSet::new(),
+        )
+    }
+
+    /// Number of paths from `from` to `to` that respect the constraints, counted with dynamic
+    /// programming over a topological order. Only nodes on some path between the two matter,
+    /// and if those contain a cycle there are endlessly many paths, so then just the paths
+    /// that visit each node at most once are counted.
+    pub fn count_paths(&self, from: N, to: N, constraints: &Constraints<N>) -> i64 {
+        if constraints.avoid.contains(&from) {
+            return 0;
+        }
+        let allowed = |n: &N| !constraints.avoid.contains(n);
+        let ahead = self.reachable(from, |n| {
+            self.successors(n).iter().copied().filter(allowed).collect()
+        });
+        if !ahead.contains(&to) {
+            return 0;
+        }
+
+        let mut predecessors = HashMap::<N, Vec<N>>::new();
+        for &n in &ahead {
+            for &s in self.successors(n) {
+                predecessors.entry(s).or_default().push(n);
+            }
+        }
+        let behind = self.reachable(to, |n| {
+            let preds = predecessors.get(&n).map_or(&[][..], Vec::as_slice);
+            preds.iter().copied().filter(allowed).collect()
+        });
+        let between = ahead.intersection(&behind).copied().collect::<HashSet<_>>();
+
+        let Some(order) = self.order_within(from, &between) else {
+            return self.count_walks(from, to, constraints, |path, n| {
+                between.contains(&n) && !path.contains(&n)
+            });
+        };
+
+        let bit = |n: N| {
+            constraints
+                .must
+                .iter()
+                .position(|&m| m == n)
+                .map_or(0, |i| 1 << i)
+        };
+        let all = (1 << constraints.must.len()) - 1;
+
+        // Paths from the start to each node, split by the required nodes they went through
+        let mut counts = HashMap::<N, HashMap<usize, i64>>::new();
+        counts.entry(from).or_default().insert(bit(from), 1);
+        for n in order {
+            let Some(here) = counts.remove(&n) else {
+                continue;
+            };
+            if n == to {
+                return here.get(&all).copied().unwrap_or(0);
+            }
+
+            for &s in self.successors(n) {
+                if !between.contains(&s) {
+                    continue;
+                }
+                let next = counts.entry(s).or_default();
+                for (&mask, &c) in &here {
+                    *next.entry(mask | bit(s)).or_default() += c;
+                }
+            }
+        }
+
+        0
+    }
+
+    /// Count paths by depth first enumeration. A step to `n` is only taken if `allow` accepts
+    /// it given the path so far, which must be enough to keep paths on cyclic graphs finite.
+    pub fn count_walks(
+        &self,
+        from: N,
+        to: N,
+        constraints: &Constraints<N>,
+        mut allow: impl FnMut(&[N], N) -> bool,
+    ) -> i64 {
+        fn walk<N: Copy + Hash + Eq>(
+            graph: &Digraph<N>,
+            path: &mut Vec<N>,
+            to: N,
+            constraints: &Constraints<N>,
+            allow: &mut impl FnMut(&[N], N) -> bool,
+        ) -> i64 {
+            let n = *path.last().unwrap();
+            if n == to {
+                return constraints.must.iter().all(|m| path.contains(m)) as i64;
+            }
+
+            let mut count = 0;
+            for &s in graph.successors(n) {
+                if constraints.avoid.contains(&s) || !allow(path, s) {
+                    continue;
+                }
+                path.push(s);
+                count += walk(graph, path, to, constraints, allow);
+                path.pop();
+            }
+            count
+        }
+
+        if constraints.avoid.contains(&from) {
+            return 0;
+        }
+        walk(self, &mut vec![from], to, constraints, &mut allow)
+    }
+}
+
+impl<N: Copy + Hash + Eq> FromIterator<(N, N)> for Digraph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(iter: I) -> Self {
+        let mut graph = Digraph::new();
+        for (a, b) in iter {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+}
+
+#[test]
+fn test() {
+    // Diamond a -> {b, c} -> d, twice in a row
+    let graph = Digraph::from_iter([
+        ('a', 'b'),
+        ('a', 'c'),
+        ('b', 'd'),
+        ('c', 'd'),
+        ('d', 'e'),
+        ('d', 'f'),
+        ('e', 'g'),
+        ('f', 'g'),
+    ]);
+    let none = Constraints::default();
+    assert_eq!(graph.topological_order('a').unwrap().len(), 7);
+    assert_eq!(graph.count_paths('a', 'g', &none), 4);
+    assert_eq!(graph.count_paths('g', 'a', &none), 0);
+
+    let must = Constraints {
+        must: vec!['f', 'b'],
+        ..Constraints::default()
+    };
+    assert_eq!(graph.count_paths('a', 'g', &must), 1);
+
+    let avoid = Constraints {
+        avoid: HashSet::from(['c']),
+        ..Constraints::default()
+    };
+    assert_eq!(graph.count_paths('a', 'g', &avoid), 2);
+    assert_eq!(graph.count_walks('a', 'g', &avoid, |_, _| true), 2);
+
+    // An undirected edge between b and c makes it cyclic
+    let mut cyclic = graph.clone();
+    cyclic.add_edge('b', 'c');
+    cyclic.add_edge('c', 'b');
+    let mut cycle = cyclic.topological_order('a').unwrap_err();
+    cycle.sort();
+    assert_eq!(cycle, ['b', 'c']);
+    assert_eq!(cyclic.count_paths('a', 'g', &none), 8);
+    assert_eq!(cyclic.count_paths('a', 'g', &must), 3);
+
+    // A cycle off to the side that never leads to the target leaves the count alone
+    let mut side = graph.clone();
+    side.add_edge('a', 'x');
+    side.add_edge('x', 'y');
+    side.add_edge('y', 'x');
+    assert!(side.topological_order('a').is_err());
+    assert_eq!(side.count_paths('a', 'g', &none), 4);
+    assert_eq!(side.count_paths('a', 'g', &must), 1);
+
+    // Allow going back and forth once
+    let twice = |path: &[char], n: char| path.iter().filter(|&&p| p == n).count() < 2;
+    assert_eq!(cyclic.count_walks('a', 'd', &none, twice), 8);
+}